@@ -0,0 +1,131 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::io::Write;
+use xml_builder::{RenderOptions, XMLElement};
+
+/// Builds a table of the given number of rows, each holding a few attributes and cells.
+fn table(rows: usize) -> XMLElement {
+    let mut table = XMLElement::new("table");
+    for index in 0..rows {
+        let mut row = XMLElement::new("row");
+        row.add_attribute("id", &index.to_string());
+        row.add_attribute("status", "active");
+        row.add_attribute("created", "2024-01-01T00:00:00Z");
+        row.add_attribute("category", "export");
+
+        for column in ["name", "quantity", "price"] {
+            let mut cell = XMLElement::new(column);
+            cell.add_attribute("type", "string");
+            cell.add_text(format!("{column} {index}")).unwrap();
+            row.add_child(cell).unwrap();
+        }
+        table.add_child(row).unwrap();
+    }
+
+    table
+}
+
+/// Builds a chain of nested elements of the given depth.
+fn nested(depth: usize) -> XMLElement {
+    let mut element = XMLElement::new("leaf");
+    for _ in 1..depth {
+        let mut parent = XMLElement::new("node");
+        parent.add_attribute("kind", "branch");
+        parent.add_child(element).unwrap();
+        element = parent;
+    }
+
+    element
+}
+
+fn render(element: &XMLElement, sort: bool) -> usize {
+    let mut writer: Vec<u8> = Vec::with_capacity(1 << 20);
+    element
+        .render(&mut writer, &RenderOptions::pretty().sort_attributes(sort))
+        .unwrap();
+
+    writer.len()
+}
+
+/// Renders the given element the way the renderer did before writing attributes and
+/// indentation in place: attributes are cloned and formatted into a String, and indentation
+/// is allocated for every tag.
+fn render_baseline(element: &XMLElement, sort: bool) -> usize {
+    fn attributes_as_string(element: &XMLElement, sort: bool) -> String {
+        let mut attributes: Vec<(String, String)> = element
+            .attributes()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        if sort {
+            attributes.sort();
+        }
+
+        let mut result = String::new();
+        for (k, v) in &attributes {
+            result = format!("{result} {k}=\"{v}\"");
+        }
+        result
+    }
+
+    fn write_element(writer: &mut Vec<u8>, element: &XMLElement, level: usize, sort: bool) {
+        let indent = "\t".repeat(level);
+        let name = element.name();
+        let attributes = attributes_as_string(element, sort);
+
+        if let Some(text) = element.text() {
+            writeln!(writer, "{indent}<{name}{attributes}>{text}</{name}>").unwrap();
+        } else if element.children().is_empty() {
+            writeln!(writer, "{indent}<{name}{attributes} />").unwrap();
+        } else {
+            writeln!(writer, "{indent}<{name}{attributes}>").unwrap();
+            for child in element.children() {
+                write_element(writer, child, level + 1, sort);
+            }
+            writeln!(writer, "{indent}</{name}>").unwrap();
+        }
+    }
+
+    let mut writer: Vec<u8> = Vec::with_capacity(1 << 20);
+    write_element(&mut writer, element, 0, sort);
+
+    writer.len()
+}
+
+fn bench_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+
+    let table = table(10_000);
+    group.throughput(Throughput::Bytes(render(&table, false) as u64));
+    group.bench_function("table", |b| b.iter(|| render(black_box(&table), false)));
+    group.bench_function("table_sorted", |b| {
+        b.iter(|| render(black_box(&table), true))
+    });
+
+    // Previous allocating path, kept as a reference for the cases above
+    assert_eq!(render_baseline(&table, true), render(&table, true));
+    group.bench_function("table_baseline", |b| {
+        b.iter(|| render_baseline(black_box(&table), false))
+    });
+    group.bench_function("table_sorted_baseline", |b| {
+        b.iter(|| render_baseline(black_box(&table), true))
+    });
+
+    // Rows are rendered once, then written from their cached rendering
+    let mut cached = table.clone();
+    for row in cached.children_mut() {
+        row.enable_render_cache();
+    }
+    group.bench_function("table_cached", |b| {
+        b.iter(|| render(black_box(&cached), false))
+    });
+
+    let nested = nested(1_000);
+    group.throughput(Throughput::Bytes(render(&nested, false) as u64));
+    group.bench_function("nested", |b| b.iter(|| render(black_box(&nested), false)));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_render);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::renderer::{CHUNK_SIZE, Renderer};
+use crate::{RenderOptions, Result, XML, XMLElement};
+
+impl XML {
+    /// Generates an XML document into the specified `AsyncWrite`.
+    ///
+    /// The document is rendered in chunks of a few kilobytes, each written once filled,
+    /// so that neither the whole document is buffered nor small writes are issued.
+    ///
+    /// Consumes the XML object.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - An object implementing `AsyncWrite` to write the document to.
+    pub async fn generate_async<W: AsyncWrite + Unpin>(self, mut writer: W) -> Result<()> {
+        let mut renderer = self.into_renderer(None)?;
+
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+        loop {
+            let remaining = renderer.fill(&mut buffer, CHUNK_SIZE)?;
+            writer.write_all(&buffer).await?;
+            buffer.clear();
+
+            if !remaining {
+                break;
+            }
+        }
+
+        writer.flush().await?;
+
+        Ok(())
+    }
+}
+
+impl XMLElement {
+    /// Renders an `XMLElement` object into the specified `AsyncWrite`.
+    ///
+    /// Does not take ownership of the object. Small writes are coalesced into chunks of
+    /// a few kilobytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - An object implementing `AsyncWrite` to render the referenced `XMLElement` to
+    /// * `options` - The `RenderOptions` to render the element with
+    pub async fn render_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<()> {
+        let mut renderer = Renderer::new(self, 0, options.clone());
+
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+        loop {
+            let mut remaining = true;
+            while remaining && buffer.len() < CHUNK_SIZE {
+                remaining = renderer.step(&mut buffer)?;
+            }
+            writer.write_all(&buffer).await?;
+            buffer.clear();
+
+            if !remaining {
+                break;
+            }
+        }
+
+        writer.flush().await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{XML, XMLVersion};
+use std::io::Write;
+
+use crate::{
+    RenderOptions, Result, XML, XMLAttributeWrapping, XMLElement, XMLEncoding, XMLLineEnding,
+    XMLStr, XMLStream, XMLVersion, XMLWriter,
+};
 
 /// Builder structure used to generate a custom XML structure.
 pub struct XMLBuilder {
@@ -19,25 +24,21 @@ pub struct XMLBuilder {
     /// Defaults to `None`
     standalone: Option<bool>,
 
-    /// Whether we want to indentate the document.
-    ///
-    /// Defaults to `true`.
-    indent: bool,
-
-    /// Whether the XML attributes should be sorted or not.
+    /// The options the document is rendered with.
     ///
-    /// Defaults to `false`.
-    sort_attributes: bool,
+    /// Defaults to `RenderOptions::pretty()`.
+    options: RenderOptions,
 
-    /// Whether we want to break lines or not.
+    /// The encoding the document is written in.
     ///
-    /// Defaults to `true`.
-    break_lines: bool,
+    /// Defaults to `UTF-8`.
+    output_encoding: XMLEncoding,
 
-    /// Whether we want to expand empty tags or not.
+    /// The minimum number of children of an element for them to be rendered concurrently.
     ///
-    /// Defaults to `false`.
-    expand_empty_tags: bool,
+    /// Defaults to `None`.
+    #[cfg(feature = "rayon")]
+    parallel: Option<usize>,
 }
 
 impl Default for XMLBuilder {
@@ -46,10 +47,10 @@ impl Default for XMLBuilder {
             version: XMLVersion::XML1_0,
             encoding: None,
             standalone: None,
-            indent: true,
-            sort_attributes: false,
-            break_lines: true,
-            expand_empty_tags: false,
+            options: RenderOptions::pretty(),
+            output_encoding: XMLEncoding::UTF8,
+            #[cfg(feature = "rayon")]
+            parallel: None,
         }
     }
 }
@@ -96,7 +97,35 @@ impl XMLBuilder {
     /// Sets the XML indentation.
     #[must_use]
     pub const fn indent(mut self, indent: bool) -> Self {
-        self.indent = indent;
+        self.options.indent = indent;
+
+        self
+    }
+
+    /// Sets the indentation to the given number of spaces per level.
+    #[must_use]
+    pub fn indent_spaces(mut self, count: usize) -> Self {
+        self.options = self.options.indent_spaces(count);
+
+        self
+    }
+
+    /// Sets the indentation to the given number of tabulations per level.
+    #[must_use]
+    pub fn indent_tabs(mut self, count: usize) -> Self {
+        self.options = self.options.indent_tabs(count);
+
+        self
+    }
+
+    /// Sets the string written once per indentation level.
+    ///
+    /// # Arguments
+    ///
+    /// `unit` - The indentation of a single level, as a static, shared or owned string.
+    #[must_use]
+    pub fn indent_string<S: Into<XMLStr>>(mut self, unit: S) -> Self {
+        self.options = self.options.indent_string(unit);
 
         self
     }
@@ -104,7 +133,7 @@ impl XMLBuilder {
     /// Enables attributes sorting.
     #[must_use]
     pub const fn sort_attributes(mut self, sort: bool) -> Self {
-        self.sort_attributes = sort;
+        self.options.sort_attributes = sort;
 
         self
     }
@@ -112,7 +141,15 @@ impl XMLBuilder {
     /// Sets whether to break lines.
     #[must_use]
     pub const fn break_lines(mut self, break_lines: bool) -> Self {
-        self.break_lines = break_lines;
+        self.options.break_lines = break_lines;
+
+        self
+    }
+
+    /// Sets the line ending written when breaking lines.
+    #[must_use]
+    pub const fn line_ending(mut self, line_ending: XMLLineEnding) -> Self {
+        self.options.line_ending = line_ending;
 
         self
     }
@@ -120,7 +157,95 @@ impl XMLBuilder {
     /// Sets whether to expand empty tags.
     #[must_use]
     pub const fn expand_empty_tags(mut self, expand_empty_tags: bool) -> Self {
-        self.expand_empty_tags = expand_empty_tags;
+        self.options.expand_empty_tags = expand_empty_tags;
+
+        self
+    }
+
+    /// Sets all the rendering options at once, replacing the ones previously set.
+    ///
+    /// # Arguments
+    ///
+    /// `options` - The `RenderOptions` the document is rendered with.
+    #[must_use]
+    pub fn render_options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+
+        self
+    }
+
+    /// Sets the width of elements kept on a single line, see [`RenderOptions::inline_width`].
+    #[must_use]
+    pub const fn inline_width(mut self, width: usize) -> Self {
+        self.options.inline_width = Some(width);
+
+        self
+    }
+
+    /// Sets the max line width of start tags, see [`RenderOptions::max_line_width`].
+    #[must_use]
+    pub const fn max_line_width(mut self, width: usize) -> Self {
+        self.options.max_line_width = Some(width);
+
+        self
+    }
+
+    /// Sets the layout of the attributes of start tags exceeding the max line width.
+    #[must_use]
+    pub const fn attribute_wrapping(mut self, attribute_wrapping: XMLAttributeWrapping) -> Self {
+        self.options.attribute_wrapping = attribute_wrapping;
+
+        self
+    }
+
+    /// Sets the width beyond which text content is wrapped, see [`RenderOptions::text_width`].
+    #[must_use]
+    pub const fn text_width(mut self, width: usize) -> Self {
+        self.options.text_width = Some(width);
+
+        self
+    }
+
+    /// Sets whether text content is re-indented, see [`RenderOptions::indent_text`].
+    #[must_use]
+    pub const fn indent_text(mut self, indent_text: bool) -> Self {
+        self.options.indent_text = indent_text;
+
+        self
+    }
+
+    /// Sets the encoding the document is written in.
+    ///
+    /// It is also declared in the XML declaration, unless an encoding attribute is set.
+    /// Characters the encoding cannot represent are written as character references in
+    /// text and attribute values, while writing them anywhere else fails.
+    #[must_use]
+    pub const fn output_encoding(mut self, output_encoding: XMLEncoding) -> Self {
+        self.output_encoding = output_encoding;
+
+        self
+    }
+
+    /// Sets the maximum depth of the rendered elements.
+    ///
+    /// Rendering a deeper element raises `XMLError::DepthLimitExceeded` instead of
+    /// writing it.
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+
+        self
+    }
+
+    /// Enables the concurrent rendering of the children of elements having at least
+    /// `min_children` children, when generating the document.
+    ///
+    /// Children are rendered by batches on the `rayon` thread pool into buffers, which
+    /// are then written in order, so that the output is identical to a sequential one.
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub const fn parallel_rendering(mut self, min_children: usize) -> Self {
+        self.parallel = Some(min_children);
 
         self
     }
@@ -128,14 +253,34 @@ impl XMLBuilder {
     /// Builds a new XML structure by consuming self.
     #[must_use]
     pub fn build(self) -> XML {
-        XML::new(
-            self.version,
-            self.encoding,
-            self.standalone,
-            self.indent,
-            self.sort_attributes,
-            self.break_lines,
-            self.expand_empty_tags,
-        )
+        let xml = XML::new(self.version, self.encoding, self.standalone, self.options)
+            .with_output_encoding(self.output_encoding);
+
+        #[cfg(feature = "rayon")]
+        let xml = xml.with_parallel(self.parallel);
+
+        xml
+    }
+
+    /// Builds a new `XMLWriter` streaming a document into the given writer.
+    ///
+    /// # Arguments
+    ///
+    /// `writer` - An object implementing `Write` to write the document to.
+    pub fn build_writer<W: Write>(self, writer: W) -> XMLWriter<W> {
+        XMLWriter::new(self.build(), writer)
+    }
+
+    /// Builds a new `XMLStream`, writing the XML declaration and the start tag of the
+    /// given root element into the given writer.
+    ///
+    /// Raises `XMLError` if the root element has content or cannot be written.
+    ///
+    /// # Arguments
+    ///
+    /// `writer` - An object implementing `Write` to write the stream to.
+    /// `root` - The root `XMLElement`, whose name and attributes are written.
+    pub fn build_stream<W: Write>(self, writer: W, root: &XMLElement) -> Result<XMLStream<W>> {
+        XMLStream::open(self.build(), writer, root)
     }
 }
@@ -0,0 +1,112 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::Stream;
+
+use crate::renderer::{CHUNK_SIZE, DocumentRenderer, Elements};
+use crate::{Result, XML, XMLElement, XMLError};
+
+/// Stream of the bytes of a document, rendered lazily in bounded-size chunks.
+///
+/// Each chunk is rendered when polled, so that the first bytes are available before the
+/// whole document is serialized and the memory usage stays flat, as needed by HTTP
+/// streaming bodies.
+pub struct XMLByteStream {
+    /// The renderer of the document.
+    renderer: DocumentRenderer<'static>,
+
+    /// The size from which a chunk is yielded.
+    chunk_size: usize,
+
+    /// Whether the whole document has been rendered.
+    done: bool,
+}
+
+impl XMLByteStream {
+    /// Sets the size from which a chunk is yielded. Defaults to 8 KiB.
+    ///
+    /// Chunks are cut between tags, so that they may exceed this size by the length of a tag,
+    /// and a size of `0` yields a chunk per tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_size` - The size of the chunks, in bytes.
+    #[must_use]
+    pub const fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+
+        self
+    }
+}
+
+impl Stream for XMLByteStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut buffer = Vec::with_capacity(this.chunk_size);
+        let chunk = match this.renderer.fill(&mut buffer, this.chunk_size) {
+            Ok(remaining) => {
+                this.done = !remaining;
+                Ok(Bytes::from(buffer))
+            }
+            Err(e) => {
+                this.done = true;
+                Err(e)
+            }
+        };
+
+        Poll::Ready(Some(chunk))
+    }
+}
+
+impl XML {
+    /// Turns the document into a `Stream` of its bytes, rendered lazily in chunks.
+    ///
+    /// Consumes the XML object.
+    ///
+    /// Raises `XMLError` if the XML declaration cannot be rendered.
+    pub fn into_stream(self) -> Result<XMLByteStream> {
+        self.into_stream_with(None)
+    }
+
+    /// Turns the document into a `Stream` of its bytes, the given elements being pulled
+    /// and rendered as children of the root element, after its own ones.
+    ///
+    /// Consumes the XML object.
+    ///
+    /// Raises `XMLError` if the document has no root element, or if its root element holds text.
+    ///
+    /// # Arguments
+    ///
+    /// * `children` - An iterator of the `XMLElement` children to render lazily.
+    pub fn into_stream_with_children<I>(self, children: I) -> Result<XMLByteStream>
+    where
+        I: IntoIterator<Item = XMLElement>,
+        I::IntoIter: Send + 'static,
+    {
+        match self.root_element() {
+            None => Err(XMLError::InsertError(
+                "Cannot insert children without a root element".into(),
+            )),
+            Some(root) if root.text().is_some() => Err(XMLError::InsertError(
+                "Cannot insert child inside an element with text".into(),
+            )),
+            Some(_) => self.into_stream_with(Some(Box::new(children.into_iter()))),
+        }
+    }
+
+    /// Internal method turning the document into a `Stream`.
+    fn into_stream_with(self, children: Option<Elements<'static>>) -> Result<XMLByteStream> {
+        Ok(XMLByteStream {
+            renderer: self.into_renderer(children)?,
+            chunk_size: CHUNK_SIZE,
+            done: false,
+        })
+    }
+}
@@ -3,19 +3,53 @@
 #![forbid(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "tokio")]
+mod asyncwriter;
 mod builder;
+#[cfg(feature = "stream")]
+mod bytestream;
+mod renderer;
+mod renderoptions;
+mod transcoding;
+mod treebuilder;
 mod utils;
 mod xml;
+mod xmlarena;
+mod xmlattributewrapping;
 mod xmlcontent;
 mod xmlelement;
+mod xmlencoding;
 mod xmlerror;
+mod xmlevent;
+mod xmllimits;
+mod xmllineending;
+mod xmlreader;
+mod xmlsplitter;
+mod xmlstr;
+mod xmlstream;
+mod xmltrivia;
 mod xmlversion;
+mod xmlwriter;
 
 pub use builder::XMLBuilder;
+#[cfg(feature = "stream")]
+pub use bytestream::XMLByteStream;
+pub use renderoptions::RenderOptions;
 pub use xml::XML;
+pub use xmlarena::{XMLArena, XMLNodeId};
+pub use xmlattributewrapping::XMLAttributeWrapping;
 pub use xmlelement::XMLElement;
+pub use xmlencoding::XMLEncoding;
 pub use xmlerror::{Result, XMLError};
+pub use xmlevent::XMLEvent;
+pub use xmllimits::XMLLimits;
+pub use xmllineending::XMLLineEnding;
+pub use xmlreader::XMLReader;
+pub use xmlsplitter::XMLSplitter;
+pub use xmlstr::XMLStr;
+pub use xmlstream::XMLStream;
 pub use xmlversion::XMLVersion;
+pub use xmlwriter::{XMLScope, XMLWriter};
 
 use utils::escape_str;
 use xmlcontent::XMLElementContent;
@@ -0,0 +1,1333 @@
+use std::io::{self, Write};
+use std::iter::Peekable;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::transcoding::EncodingWriter;
+use crate::xmlarena::Node as ArenaNode;
+use crate::xmltrivia::XMLTrivia;
+use crate::{
+    RenderOptions, Result, XMLArena, XMLAttributeWrapping, XMLElement, XMLElementContent,
+    XMLEncoding, XMLError, XMLLineEnding, XMLStr,
+};
+
+/// Size of the chunks of bytes rendered at once when writing a document.
+pub const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Number of children rendered concurrently per thread, when rendering in parallel.
+#[cfg(feature = "rayon")]
+const PARALLEL_BATCH: usize = 16;
+
+/// Tabulations written at once when indenting.
+const TABS: &[u8; 32] = b"\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t";
+
+/// Spaces written at once when indenting.
+const SPACES: &[u8; 32] = b"                                ";
+
+/// Iterator over elements rendered lazily, such as the children handed over by owned elements.
+pub type Elements<'a> = Box<dyn Iterator<Item = XMLElement> + Send + 'a>;
+
+/// An element being rendered, either borrowed, owned by the renderer, the child at the
+/// given index of children shared with other elements, or the node at the given index of
+/// an arena.
+enum Node<'a> {
+    Borrowed(&'a XMLElement),
+    Owned(XMLElement),
+    Shared(Arc<Vec<XMLElement>>, usize),
+    Arena(&'a XMLArena, usize),
+}
+
+/// The data of an element being rendered, depending on where it is stored.
+enum View<'b> {
+    Element(&'b XMLElement),
+    Arena(&'b ArenaNode),
+}
+
+impl Node<'_> {
+    fn view(&self) -> View<'_> {
+        match self {
+            Self::Borrowed(element) => View::Element(element),
+            Self::Owned(element) => View::Element(element),
+            Self::Shared(elements, index) => View::Element(&elements[*index]),
+            Self::Arena(arena, index) => View::Arena(arena.node(*index)),
+        }
+    }
+
+    /// Returns the element, unless the node is stored in an arena, which only holds the
+    /// name, attributes and content of its elements.
+    fn element(&self) -> Option<&XMLElement> {
+        match self.view() {
+            View::Element(element) => Some(element),
+            View::Arena(_) => None,
+        }
+    }
+
+    /// Returns a node borrowing the same element.
+    fn borrow(&self) -> Node<'_> {
+        match self {
+            Self::Arena(arena, index) => Node::Arena(arena, *index),
+            Self::Borrowed(element) => Node::Borrowed(element),
+            Self::Owned(element) => Node::Borrowed(element),
+            Self::Shared(elements, index) => Node::Borrowed(&elements[*index]),
+        }
+    }
+
+    fn name(&self) -> &XMLStr {
+        match self.view() {
+            View::Element(element) => &element.name,
+            View::Arena(node) => &node.name,
+        }
+    }
+
+    fn attributes(&self) -> &[(XMLStr, XMLStr)] {
+        match self.view() {
+            View::Element(element) => &element.attributes,
+            View::Arena(node) => &node.attributes,
+        }
+    }
+
+    fn sort_attributes(&self) -> Option<bool> {
+        match self.view() {
+            View::Element(element) => element.sort_attributes,
+            View::Arena(node) => node.sort_attributes,
+        }
+    }
+
+    fn text(&self) -> Option<&str> {
+        match self.view() {
+            View::Element(element) => element.text(),
+            View::Arena(node) => node.text.as_deref(),
+        }
+    }
+
+    /// Returns whether the element has child elements, not counting lazy ones.
+    fn has_children(&self) -> bool {
+        match self.view() {
+            View::Element(element) => !element.children().is_empty(),
+            View::Arena(node) => node.first_child.is_some(),
+        }
+    }
+
+    /// Returns the indentation, line breaks and empty tags expansion overridden by the element.
+    fn overrides(&self) -> (Option<bool>, Option<bool>, Option<bool>) {
+        match self.view() {
+            View::Element(element) => (
+                element.indent,
+                element.break_lines,
+                element.expand_empty_tags,
+            ),
+            View::Arena(node) => (node.indent, node.break_lines, node.expand_empty_tags),
+        }
+    }
+
+    fn trivia(&self) -> Option<&XMLTrivia> {
+        self.element()?.trivia.as_deref()
+    }
+}
+
+/// The children of an element whose start tag has been rendered.
+struct Children<'a> {
+    /// Children borrowed from the element.
+    borrowed: std::slice::Iter<'a, XMLElement>,
+
+    /// Children shared with other elements, along with the index of the next one.
+    ///
+    /// They are rendered without being copied, after the borrowed ones.
+    shared: Option<(Arc<Vec<XMLElement>>, usize)>,
+
+    /// Children owned by the renderer, pulled once the other ones are rendered.
+    owned: Peekable<Elements<'a>>,
+
+    /// Children stored in an arena, along with the next one, linked to its siblings.
+    arena: Option<(&'a XMLArena, Option<usize>)>,
+}
+
+impl<'a> Children<'a> {
+    fn next(&mut self) -> Option<Node<'a>> {
+        if let Some((arena, next)) = &mut self.arena
+            && let Some(index) = *next
+        {
+            *next = arena.node(index).next_sibling;
+            return Some(Node::Arena(arena, index));
+        }
+        if let Some(child) = self.borrowed.next() {
+            return Some(Node::Borrowed(child));
+        }
+        if let Some((elements, index)) = &mut self.shared
+            && *index < elements.len()
+        {
+            *index += 1;
+            return Some(Node::Shared(Arc::clone(elements), *index - 1));
+        }
+
+        self.owned.next().map(Node::Owned)
+    }
+
+    /// Returns the number of children not rendered yet, at least.
+    #[cfg(feature = "rayon")]
+    fn len(&self) -> usize {
+        let shared = self
+            .shared
+            .as_ref()
+            .map_or(0, |(elements, index)| elements.len() - index);
+
+        self.borrowed.len() + shared + self.owned.size_hint().0
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.borrowed.len() == 0
+            && self
+                .shared
+                .as_ref()
+                .is_none_or(|(elements, index)| *index >= elements.len())
+            && self.owned.peek().is_none()
+            && self.arena.is_none_or(|(_, next)| next.is_none())
+    }
+}
+
+/// The layout options of an element, inherited by its descendants unless they override them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    /// Whether elements are indented.
+    indent: bool,
+
+    /// Whether lines are broken after each element.
+    break_lines: bool,
+
+    /// Whether empty elements are written with an end tag.
+    expand_empty_tags: bool,
+
+    /// Whether whitespace is preserved by `xml:space`, if text content is laid out.
+    preserve_space: Option<bool>,
+}
+
+impl Layout {
+    /// Returns the layout of the given element, inheriting this one unless overridden.
+    ///
+    /// Elements disabling line breaks disable indentation as well, unless they enable it,
+    /// as their content is written on a single line.
+    fn of(self, node: &Node<'_>) -> Self {
+        let (indent, break_lines, expand_empty_tags) = node.overrides();
+        let indent = match (indent, break_lines) {
+            (Some(indent), _) => indent,
+            (None, Some(false)) => false,
+            (None, _) => self.indent,
+        };
+
+        Self {
+            indent,
+            break_lines: break_lines.unwrap_or(self.break_lines),
+            expand_empty_tags: expand_empty_tags.unwrap_or(self.expand_empty_tags),
+            preserve_space: self
+                .preserve_space
+                .map(|preserve| preserved_space(node.attributes()).unwrap_or(preserve)),
+        }
+    }
+}
+
+/// An element whose start tag may have been rendered, but not its end tag.
+struct Frame<'a> {
+    /// The element being rendered.
+    element: Node<'a>,
+
+    /// The indentation level of the element.
+    level: usize,
+
+    /// Whether indentation and line breaks apply, which is not the case inside elements
+    /// rendered with their original formatting.
+    formatted: bool,
+
+    /// The layout of the parent element, which the element is laid out within.
+    outer: Layout,
+
+    /// The layout of the element, which its content is laid out with.
+    layout: Layout,
+
+    /// The children not rendered yet, set once the start tag is rendered.
+    children: Option<Children<'a>>,
+
+    /// The number of children already rendered.
+    rendered: usize,
+
+    /// Children rendered after the element ones, only set for the root of an owned rendering.
+    appended: Option<Elements<'a>>,
+
+    /// Whether lazy or appended children have been pulled to render the element.
+    pulled: bool,
+
+    /// Whether the children of the element are rendered concurrently.
+    #[cfg(feature = "rayon")]
+    parallel: bool,
+}
+
+impl<'a> Frame<'a> {
+    fn new(element: Node<'a>, level: usize, formatted: bool, outer: Layout) -> Self {
+        let layout = outer.of(&element);
+
+        Self {
+            element,
+            level,
+            formatted,
+            outer,
+            layout,
+            children: None,
+            rendered: 0,
+            appended: None,
+            pulled: false,
+            #[cfg(feature = "rayon")]
+            parallel: false,
+        }
+    }
+}
+
+/// Incremental renderer of an element, rendering one tag at a time.
+///
+/// Elements are tracked with an explicit stack instead of recursive calls, so that the
+/// rendering can be suspended between two steps, whatever the depth of the tree.
+pub struct Renderer<'a> {
+    /// The elements being rendered, the innermost being the last one.
+    stack: Vec<Frame<'a>>,
+
+    /// The options to apply.
+    options: RenderOptions,
+
+    /// The number of ancestors of the rendered element, accounted for in its depth.
+    ancestors: usize,
+
+    /// The bytes rendered since the outermost cached element started.
+    record: Vec<u8>,
+
+    /// The recordings of the cached elements being rendered, the innermost being the last one.
+    recordings: Vec<Recording>,
+
+    /// The recording of the cached element rendered by the last step, if any.
+    recorded: Option<Recording>,
+
+    /// The number of lazy children sources pulled so far.
+    lazy: usize,
+
+    /// Whether the rendering is only measured, which must not consume lazy children.
+    measuring: bool,
+
+    /// The minimum number of children of an element for them to be rendered concurrently.
+    #[cfg(feature = "rayon")]
+    parallel: Option<usize>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Instantiates a new `Renderer` of the given element, indented at the given level.
+    pub fn new(element: &'a XMLElement, level: usize, options: RenderOptions) -> Self {
+        Self::from_frame(
+            Frame::new(Node::Borrowed(element), level, true, options.layout()),
+            level,
+            options,
+        )
+    }
+
+    /// Instantiates a new `Renderer` consuming the given element while rendering it.
+    ///
+    /// The given children are pulled and rendered after the element ones.
+    pub fn owned(
+        element: XMLElement,
+        appended: Option<Elements<'a>>,
+        options: RenderOptions,
+    ) -> Self {
+        let mut frame = Frame::new(Node::Owned(element), 0, true, options.layout());
+        frame.appended = appended;
+
+        Self::from_frame(frame, 0, options)
+    }
+
+    /// Instantiates a new `Renderer` of the given element stored in an arena.
+    pub fn arena(arena: &'a XMLArena, index: usize, options: RenderOptions) -> Self {
+        Self::from_frame(
+            Frame::new(Node::Arena(arena, index), 0, true, options.layout()),
+            0,
+            options,
+        )
+    }
+
+    /// Internal method instantiating a new `Renderer` of the given frame, with the given
+    /// number of ancestors.
+    fn from_frame(frame: Frame<'a>, ancestors: usize, options: RenderOptions) -> Self {
+        Self {
+            stack: vec![frame],
+            options,
+            ancestors,
+            record: Vec::new(),
+            recordings: Vec::new(),
+            recorded: None,
+            lazy: 0,
+            measuring: false,
+            #[cfg(feature = "rayon")]
+            parallel: None,
+        }
+    }
+
+    /// Makes the rendering fail instead of consuming lazy children, so that it can be
+    /// measured without altering the rendered elements.
+    pub const fn measuring(mut self) -> Self {
+        self.measuring = true;
+
+        self
+    }
+
+    /// Sets the minimum number of children of an element for them to be rendered
+    /// concurrently.
+    #[cfg(feature = "rayon")]
+    pub const fn with_parallel(mut self, parallel: Option<usize>) -> Self {
+        self.parallel = parallel;
+
+        self
+    }
+
+    /// Renders the whole element into the given writer.
+    pub fn render<W: Write>(mut self, writer: &mut W) -> Result<()> {
+        while self.step(writer)? {}
+
+        Ok(())
+    }
+
+    /// Renders the next tag into the given writer.
+    ///
+    /// Returns whether some tags remain to be rendered.
+    pub fn step<W: Write>(&mut self, writer: &mut W) -> Result<bool> {
+        // Bytes are only recorded while cached elements are rendered
+        if self.recordings.is_empty() {
+            let remaining = self.step_into(writer);
+            self.start_recording();
+            return remaining;
+        }
+
+        // Tags are rendered into the record, then copied to the writer
+        let mut record = std::mem::take(&mut self.record);
+        let written = record.len();
+        let remaining = self.step_into(&mut record);
+        writer.write_all(&record[written..])?;
+        self.record = record;
+        self.start_recording();
+
+        if let Some(recording) = self.recorded.take()
+            && let Some(start) = recording.start
+            && recording.lazy == self.lazy
+        {
+            // Lazy children are consumed, so that elements pulling some cannot be replayed
+            recording.cache.set(recording.key, &self.record[start..]);
+        }
+        if self.recordings.is_empty() {
+            self.record.clear();
+        }
+
+        remaining
+    }
+
+    /// Internal method starting the recording of the element known not to be cached by
+    /// the last step, if any.
+    fn start_recording(&mut self) {
+        if let Some(recording) = self.recordings.last_mut()
+            && recording.start.is_none()
+        {
+            recording.start = Some(self.record.len());
+        }
+    }
+
+    /// Internal method rendering the next tag into the given writer.
+    fn step_into<W: Write>(&mut self, writer: &mut W) -> Result<bool> {
+        let depth = self.ancestors + self.stack.len();
+        let Some(frame) = self.stack.last_mut() else {
+            return Ok(false);
+        };
+
+        let Some(children) = &mut frame.children else {
+            if let Some(max_depth) = self.options.max_depth
+                && depth > max_depth
+            {
+                return Err(XMLError::DepthLimitExceeded(max_depth));
+            }
+
+            let node = &frame.element;
+            if self.measuring && node.element().is_some_and(XMLElement::has_lazy_children) {
+                return Err(XMLError::MeasureError(format!(
+                    "element `{}` has lazy children, which would be consumed",
+                    node.name()
+                )));
+            }
+
+            // Cached elements are written at once, or recorded from the next step on
+            if let Some(cache) = node.element().and_then(XMLElement::render_cache)
+                && self
+                    .recordings
+                    .last()
+                    .is_none_or(|recording| recording.depth != depth)
+            {
+                let key = CacheKey {
+                    options: self.options.clone(),
+                    sort_attributes: node.sort_attributes(),
+                    level: frame.level,
+                    formatted: frame.formatted,
+                    outer: frame.outer,
+                    layout: frame.layout,
+                    depth,
+                };
+                if let Some(bytes) = cache.get(&key) {
+                    writer.write_all(&bytes)?;
+                    self.stack.pop();
+                    return Ok(!self.stack.is_empty());
+                }
+
+                self.recordings.push(Recording {
+                    key,
+                    cache: Arc::clone(cache),
+                    depth,
+                    start: None,
+                    lazy: self.lazy,
+                });
+                return Ok(true);
+            }
+
+            // Elements fitting on a single line lay out their content compactly
+            if self.options.fits_inline(frame, depth - 1) {
+                frame.layout.indent = false;
+                frame.layout.break_lines = false;
+            }
+
+            let rendered = self.options.start(frame, writer)?;
+            if frame.pulled {
+                self.lazy += 1;
+            }
+            #[cfg(feature = "rayon")]
+            {
+                // Children laid out by their original formatting are rendered sequentially
+                frame.parallel = !rendered
+                    && frame.element.trivia().is_none()
+                    && self.parallel.is_some_and(|min_children| {
+                        frame
+                            .children
+                            .as_ref()
+                            .is_some_and(|children| children.len() >= min_children)
+                    });
+            }
+            if rendered {
+                self.finish(depth);
+            }
+            return Ok(!self.stack.is_empty());
+        };
+
+        #[cfg(feature = "rayon")]
+        if frame.parallel {
+            let batch: Vec<Node<'a>> = std::iter::from_fn(|| children.next())
+                .take(rayon::current_num_threads() * PARALLEL_BATCH)
+                .collect();
+            if !batch.is_empty() {
+                frame.rendered += batch.len();
+                let (level, formatted, layout) = (frame.level + 1, frame.formatted, frame.layout);
+                return self.render_batch(batch, level, formatted, layout, depth, writer);
+            }
+        }
+
+        let child = children.next();
+
+        // Children of elements with their original formatting are laid out by their gaps
+        let trivia = frame.element.trivia();
+        if let Some(trivia) = trivia {
+            write!(
+                writer,
+                "{}",
+                trivia.gaps.get(frame.rendered).map_or("", String::as_str)
+            )?;
+        }
+        let original = trivia.is_some();
+
+        match child {
+            Some(child) => {
+                frame.rendered += 1;
+                let level = if original { 0 } else { frame.level + 1 };
+                let formatted = frame.formatted && !original;
+                let frame = Frame::new(child, level, formatted, frame.layout);
+                self.stack.push(frame);
+            }
+            None => {
+                self.options.end(frame, writer)?;
+                self.finish(depth);
+            }
+        }
+
+        Ok(!self.stack.is_empty())
+    }
+
+    /// Internal method rendering the given sibling elements concurrently into buffers,
+    /// then writing them in order into the given writer.
+    #[cfg(feature = "rayon")]
+    fn render_batch<W: Write>(
+        &mut self,
+        batch: Vec<Node<'a>>,
+        level: usize,
+        formatted: bool,
+        outer: Layout,
+        ancestors: usize,
+        writer: &mut W,
+    ) -> Result<bool> {
+        use rayon::prelude::*;
+
+        let (options, parallel) = (&self.options, self.parallel);
+        let rendered: Vec<Result<(Vec<u8>, usize)>> = batch
+            .into_par_iter()
+            .map(|node| {
+                let frame = Frame::new(node, level, formatted, outer);
+                let mut renderer =
+                    Self::from_frame(frame, ancestors, options.clone()).with_parallel(parallel);
+
+                let mut buffer = Vec::new();
+                while renderer.step(&mut buffer)? {}
+
+                Ok((buffer, renderer.lazy))
+            })
+            .collect();
+
+        for result in rendered {
+            let (buffer, lazy) = result?;
+            writer.write_all(&buffer)?;
+            self.lazy += lazy;
+        }
+
+        Ok(true)
+    }
+
+    /// Internal method popping the element fully rendered at the given depth, handing its
+    /// recording over if it is cached.
+    fn finish(&mut self, depth: usize) {
+        self.stack.pop();
+
+        if self
+            .recordings
+            .last()
+            .is_some_and(|recording| recording.depth == depth)
+        {
+            self.recorded = self.recordings.pop();
+        }
+    }
+}
+
+/// The rendering context of an element, which its cached rendering is valid for.
+#[derive(Clone, PartialEq, Eq)]
+struct CacheKey {
+    /// The options applied.
+    options: RenderOptions,
+
+    /// The attributes sorting option of the element itself.
+    sort_attributes: Option<bool>,
+
+    /// The indentation level of the element.
+    level: usize,
+
+    /// Whether indentation and line breaks apply.
+    formatted: bool,
+
+    /// The layout of the parent element.
+    outer: Layout,
+
+    /// The layout of the element, overridden by its own options.
+    layout: Layout,
+
+    /// The depth of the element, checked against the depth limit.
+    depth: usize,
+}
+
+/// The recording of a cached element being rendered.
+struct Recording {
+    /// The rendering context of the element.
+    key: CacheKey,
+
+    /// The renderings of the element.
+    cache: Arc<RenderCache>,
+
+    /// The depth of the element.
+    depth: usize,
+
+    /// The offset of the element in the recorded bytes, once its rendering started.
+    start: Option<usize>,
+
+    /// The number of lazy children sources pulled before the element started.
+    lazy: usize,
+}
+
+/// Renderings of an element, reused as long as it is not mutated.
+#[derive(Default)]
+pub struct RenderCache {
+    /// The rendered bytes of the element, per rendering context.
+    renderings: Mutex<Vec<(CacheKey, Arc<[u8]>)>>,
+}
+
+impl RenderCache {
+    /// Returns the rendering of the element in the given context, if cached.
+    fn get(&self, key: &CacheKey) -> Option<Arc<[u8]>> {
+        self.renderings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(cached, _)| cached == key)
+            .map(|(_, bytes)| Arc::clone(bytes))
+    }
+
+    /// Caches the rendering of the element in the given context.
+    fn set(&self, key: CacheKey, bytes: &[u8]) {
+        let mut renderings = self
+            .renderings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        renderings.retain(|(cached, _)| *cached != key);
+        renderings.push((key, bytes.into()));
+    }
+}
+
+impl RenderOptions {
+    /// Internal method returning the layout of the rendered element, unless it overrides it.
+    const fn layout(&self) -> Layout {
+        Layout {
+            indent: self.indent,
+            break_lines: self.break_lines,
+            expand_empty_tags: self.expand_empty_tags,
+            preserve_space: if self.lays_out_text() {
+                Some(false)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Internal method returning whether text content is wrapped or re-indented.
+    pub(crate) const fn lays_out_text(&self) -> bool {
+        self.text_width.is_some() || self.indent_text
+    }
+
+    /// Internal method returning the width of the indentation of the given level.
+    fn indent_width(&self, level: usize, indented: bool) -> usize {
+        if indented {
+            level * self.indent_unit.chars().count()
+        } else {
+            0
+        }
+    }
+
+    /// Internal method returning the width left to an element indented at the given level,
+    /// if elements are kept on a single line when they fit.
+    pub(crate) fn inline_limit(&self, level: usize, indented: bool) -> Option<usize> {
+        self.inline_width?
+            .checked_sub(self.indent_width(level, indented))
+    }
+
+    /// Internal method returning the bytes separating the given attributes of a start tag of
+    /// the given name, indented at the given level and ending with the given characters, if
+    /// the tag exceeds the max line width.
+    ///
+    /// Returns `None` when the attributes are separated by a single space.
+    pub(crate) fn attribute_separator(
+        &self,
+        name: &str,
+        attributes: &[(XMLStr, XMLStr)],
+        level: usize,
+        indented: bool,
+        end: &str,
+    ) -> Option<Vec<u8>> {
+        let max_line_width = self.max_line_width?;
+        if attributes.len() < 2 {
+            return None;
+        }
+
+        let indent = self.indent_width(level, indented);
+        if indent + start_tag_width(name, attributes) + end.chars().count() <= max_line_width {
+            return None;
+        }
+        let name = name.chars().count();
+
+        let mut separator = Vec::from(self.line_ending.as_str());
+        match self.attribute_wrapping {
+            XMLAttributeWrapping::Aligned => {
+                let level = if indented { level } else { 0 };
+                write_indent(&mut separator, &self.indent_unit, level).ok()?;
+                write_indent(&mut separator, " ", name + 2).ok()?;
+            }
+            XMLAttributeWrapping::Indented => {
+                let level = if indented { level + 1 } else { 1 };
+                write_indent(&mut separator, &self.indent_unit, level).ok()?;
+            }
+        }
+
+        Some(separator)
+    }
+
+    /// Internal method writing the given text content of an element of the given name and
+    /// attributes, indented at the given level, right after its start tag.
+    ///
+    /// Text that is wrapped or re-indented is written on its own lines one level deeper,
+    /// followed by the indentation of the end tag. Other text is written as is.
+    pub(crate) fn write_text<W: Write>(
+        &self,
+        writer: &mut W,
+        name: &str,
+        attributes: &[(XMLStr, XMLStr)],
+        text: &str,
+        level: usize,
+        indented: bool,
+    ) -> io::Result<()> {
+        let multiline = text.contains('\n');
+        let exceeds = |width: usize| {
+            let tags = start_tag_width(name, attributes) + ">".len() + name.chars().count() + 3;
+            self.indent_width(level, indented) + tags + text.chars().count() > width
+        };
+        let laid_out = if multiline {
+            self.lays_out_text()
+        } else {
+            self.text_width.is_some_and(exceeds)
+        };
+        let lines = if laid_out { dedent(text) } else { Vec::new() };
+        if lines.is_empty() {
+            return writer.write_all(text.as_bytes());
+        }
+
+        let indent = self.indent_width(level + 1, indented);
+        let width = self.text_width.map(|width| width.saturating_sub(indent));
+        write_line_ending(writer, self.line_ending)?;
+        for line in lines {
+            if !line.is_empty() {
+                self.write_text_line(writer, line, level + 1, indented, width)?;
+            }
+            write_line_ending(writer, self.line_ending)?;
+        }
+        if indented {
+            write_indent(writer, &self.indent_unit, level)?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing the given line of text indented at the given level, wrapped
+    /// on several lines if it exceeds the given width.
+    ///
+    /// Wrapped lines keep the leading whitespace of the line, and end without line break.
+    fn write_text_line<W: Write>(
+        &self,
+        writer: &mut W,
+        line: &str,
+        level: usize,
+        indented: bool,
+        width: Option<usize>,
+    ) -> io::Result<()> {
+        if indented {
+            write_indent(writer, &self.indent_unit, level)?;
+        }
+        let Some(width) = width.filter(|&width| line.chars().count() > width) else {
+            return writer.write_all(line.as_bytes());
+        };
+
+        let lead = lead_of(line);
+        let words = &line[lead.len()..];
+        let lead_width = lead.chars().count();
+        writer.write_all(lead.as_bytes())?;
+        let mut column = None;
+        for word in words.split([' ', '\t']).filter(|word| !word.is_empty()) {
+            let length = word.chars().count();
+            column = match column {
+                None => Some(lead_width + length),
+                Some(column) if column + 1 + length <= width => {
+                    writer.write_all(b" ")?;
+                    Some(column + 1 + length)
+                }
+                Some(_) => {
+                    write_line_ending(writer, self.line_ending)?;
+                    if indented {
+                        write_indent(writer, &self.indent_unit, level)?;
+                    }
+                    writer.write_all(lead.as_bytes())?;
+                    Some(lead_width + length)
+                }
+            };
+            writer.write_all(word.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method returning whether the given element, whose start tag is not rendered
+    /// yet, fits on a single line.
+    ///
+    /// The element is measured without its trailing line break, stopping as soon as it
+    /// exceeds the width left, so that large elements are not fully measured.
+    fn fits_inline(&self, frame: &Frame<'_>, ancestors: usize) -> bool {
+        let node = &frame.element;
+        if self.inline_width.is_none()
+            || !frame.formatted
+            || !frame.layout.break_lines
+            || !node.has_children()
+            || node.trivia().is_some()
+            || frame.appended.is_some()
+        {
+            return false;
+        }
+        let Some(limit) = self.inline_limit(frame.level, frame.outer.indent) else {
+            return false;
+        };
+
+        let compact = Layout {
+            indent: false,
+            break_lines: false,
+            ..frame.layout
+        };
+        let frame = Frame::new(node.borrow(), frame.level, true, compact);
+        // Descendants are not measured again, overrides breaking lines making them not fit
+        let options = Self {
+            inline_width: None,
+            ..self.clone()
+        };
+        Renderer::from_frame(frame, ancestors, options)
+            .measuring()
+            .render(&mut WidthWriter(limit))
+            .is_ok()
+    }
+
+    /// Internal method rendering the start tag of the given element, or the whole element
+    /// if it has no children.
+    ///
+    /// Returns whether the whole element has been rendered.
+    fn start<W: Write>(&self, frame: &mut Frame<'_>, writer: &mut W) -> Result<bool> {
+        // Owned elements hand their children over unless shared, and lazy children are only
+        // known once pulled
+        let lazy = frame
+            .element
+            .element()
+            .and_then(XMLElement::take_lazy_children);
+        let mut borrowed = [].iter();
+        let mut shared = None;
+        let mut arena = None;
+        let mut owned: Elements<'_> = Box::new(std::iter::empty());
+        match &mut frame.element {
+            Node::Borrowed(element) => borrowed = (*element).children().iter(),
+            Node::Owned(element) => {
+                match std::mem::replace(&mut element.content, XMLElementContent::Empty) {
+                    XMLElementContent::Elements(children) => match Arc::try_unwrap(children) {
+                        Ok(children) => owned = Box::new(children.into_iter()),
+                        Err(children) => shared = Some((children, 0)),
+                    },
+                    content => element.content = content,
+                }
+            }
+            Node::Shared(elements, index) => {
+                if let XMLElementContent::Elements(children) = &elements[*index].content {
+                    shared = Some((Arc::clone(children), 0));
+                }
+            }
+            Node::Arena(nodes, index) => arena = Some((*nodes, nodes.node(*index).first_child)),
+        }
+        frame.pulled = lazy.is_some() || frame.appended.is_some();
+        for source in [lazy, frame.appended.take()].into_iter().flatten() {
+            owned = Box::new(owned.chain(source));
+        }
+
+        let children = frame.children.insert(Children {
+            borrowed,
+            shared,
+            owned: owned.peekable(),
+            arena,
+        });
+        let empty = children.is_empty();
+        let node = &frame.element;
+
+        if let Some(trivia) = node.trivia() {
+            write!(writer, "<{}", node.name())?;
+            for (index, (key, value)) in node.attributes().iter().enumerate() {
+                match trivia.attributes.get(index) {
+                    Some(attribute) => write!(
+                        writer,
+                        "{}{key}{}{quote}{value}{quote}",
+                        attribute.prefix,
+                        attribute.separator,
+                        quote = attribute.quote
+                    )?,
+                    None => write!(writer, " {key}=\"{value}\"")?,
+                }
+            }
+            write!(writer, "{}", trivia.start_tag_end)?;
+
+            match node.text() {
+                Some(text) => {
+                    write!(writer, ">{text}</{}{}>", node.name(), trivia.end_tag_end)?;
+                    return Ok(true);
+                }
+                _ if empty && trivia.self_closing => {
+                    write!(writer, "/>")?;
+                    return Ok(true);
+                }
+                _ => write!(writer, ">")?,
+            }
+        } else {
+            self.write_indent(frame, frame.outer, writer)?;
+            writer.write_all(b"<")?;
+            writer.write_all(node.name().as_bytes())?;
+            let sort_attributes = node.sort_attributes().unwrap_or(self.sort_attributes);
+            // Start tags are only wrapped when laid out on their own lines
+            let wrapped = self.max_line_width.is_some()
+                && frame.formatted
+                && frame.outer.break_lines
+                && frame.layout.break_lines;
+            let separator = if wrapped {
+                let closed = empty && node.text().is_none();
+                let end = if closed && !frame.layout.expand_empty_tags {
+                    " />"
+                } else {
+                    ">"
+                };
+                self.attribute_separator(
+                    node.name(),
+                    node.attributes(),
+                    frame.level,
+                    frame.outer.indent,
+                    end,
+                )
+            } else {
+                None
+            };
+            let separator = separator.as_deref();
+            write_attributes(writer, node.attributes(), sort_attributes, separator)?;
+
+            match node.text() {
+                Some(text) => {
+                    writer.write_all(b">")?;
+                    // Whitespace preserved by `xml:space` is written as is
+                    if frame.formatted
+                        && frame.layout.break_lines
+                        && frame.layout.preserve_space == Some(false)
+                    {
+                        self.write_text(
+                            writer,
+                            node.name(),
+                            node.attributes(),
+                            text,
+                            frame.level,
+                            frame.layout.indent,
+                        )?;
+                    } else {
+                        writer.write_all(text.as_bytes())?;
+                    }
+                    writer.write_all(b"</")?;
+                    writer.write_all(node.name().as_bytes())?;
+                    writer.write_all(b">")?;
+                }
+                _ if empty && frame.layout.expand_empty_tags => {
+                    writer.write_all(b"></")?;
+                    writer.write_all(node.name().as_bytes())?;
+                    writer.write_all(b">")?;
+                }
+                _ if empty => writer.write_all(b" />")?,
+                _ => writer.write_all(b">")?,
+            }
+
+            // Whole elements are followed by the line break of their parent
+            let whole = empty || node.text().is_some();
+            let layout = if whole { frame.outer } else { frame.layout };
+            self.write_line_break(frame, layout, writer)?;
+            if whole {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Internal method rendering the end tag of the given element.
+    fn end<W: Write>(&self, frame: &Frame<'_>, writer: &mut W) -> Result<()> {
+        let node = &frame.element;
+
+        match node.trivia() {
+            Some(trivia) => write!(writer, "</{}{}>", node.name(), trivia.end_tag_end)?,
+            None => {
+                self.write_indent(frame, frame.layout, writer)?;
+                writer.write_all(b"</")?;
+                writer.write_all(node.name().as_bytes())?;
+                writer.write_all(b">")?;
+                self.write_line_break(frame, frame.outer, writer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing the indentation of a tag of the given element laid out with
+    /// the given layout, if any.
+    fn write_indent<W: Write>(
+        &self,
+        frame: &Frame<'_>,
+        layout: Layout,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        if layout.indent && frame.formatted {
+            write_indent(writer, &self.indent_unit, frame.level)?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing the line break following a tag of the given element laid out
+    /// with the given layout, if any.
+    fn write_line_break<W: Write>(
+        &self,
+        frame: &Frame<'_>,
+        layout: Layout,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        if layout.break_lines && frame.formatted {
+            write_line_ending(writer, self.line_ending)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the given line ending.
+///
+/// Each line ending is written as a literal, so that writing a single byte is optimized.
+pub fn write_line_ending<W: Write>(writer: &mut W, line_ending: XMLLineEnding) -> io::Result<()> {
+    match line_ending {
+        XMLLineEnding::LF => writer.write_all(b"\n"),
+        XMLLineEnding::CRLF => writer.write_all(b"\r\n"),
+        XMLLineEnding::CR => writer.write_all(b"\r"),
+    }
+}
+
+/// Writes the indentation of the given level, made of the given unit, without allocating it.
+///
+/// Units made of tabulations or spaces only are written by chunks, other ones once per level.
+pub fn write_indent<W: Write>(writer: &mut W, unit: &str, level: usize) -> io::Result<()> {
+    let unit = unit.as_bytes();
+    let chunk: &[u8] = match unit.first() {
+        Some(b'\t') if unit.iter().all(|&byte| byte == b'\t') => TABS,
+        Some(b' ') if unit.iter().all(|&byte| byte == b' ') => SPACES,
+        _ => {
+            for _ in 0..level {
+                writer.write_all(unit)?;
+            }
+            return Ok(());
+        }
+    };
+
+    let mut remaining = level * unit.len();
+    while remaining > 0 {
+        let length = remaining.min(chunk.len());
+        writer.write_all(&chunk[..length])?;
+        remaining -= length;
+    }
+
+    Ok(())
+}
+
+/// Returns the width of a start tag of the given name and attributes, without its end.
+fn start_tag_width(name: &str, attributes: &[(XMLStr, XMLStr)]) -> usize {
+    // Each attribute is written as ` key="value"`
+    let attributes = attributes
+        .iter()
+        .map(|(key, value)| key.chars().count() + value.chars().count() + 4)
+        .sum::<usize>();
+
+    "<".len() + name.chars().count() + attributes
+}
+
+/// Returns whether the given attributes preserve whitespace, if they set `xml:space`.
+pub fn preserved_space(attributes: &[(XMLStr, XMLStr)]) -> Option<bool> {
+    attributes
+        .iter()
+        .find(|(key, _)| key.as_str() == "xml:space")
+        .map(|(_, value)| value.as_str() == "preserve")
+}
+
+/// Returns the lines of the given text, without the blank lines surrounding them nor their
+/// common leading whitespace. Blank lines in between are returned empty.
+///
+/// The common leading whitespace is the longest one starting all the lines, so that lines
+/// indented with different mixes of tabulations and spaces are not dedented.
+fn dedent(text: &str) -> Vec<&str> {
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let blank = |line: &&str| line.trim_matches([' ', '\t']).is_empty();
+    let (Some(first), Some(last)) = (
+        lines.iter().position(|line| !blank(line)),
+        lines.iter().rposition(|line| !blank(line)),
+    ) else {
+        return Vec::new();
+    };
+
+    let lines = &lines[first..=last];
+    let common = lines
+        .iter()
+        .filter(|line| !blank(line))
+        .map(|line| lead_of(line))
+        .reduce(|common, lead| {
+            let length = common
+                .bytes()
+                .zip(lead.bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            &common[..length]
+        })
+        .map_or(0, str::len);
+
+    lines
+        .iter()
+        .map(|line| if blank(line) { "" } else { &line[common..] })
+        .collect()
+}
+
+/// Returns the leading spaces and tabulations of the given line.
+fn lead_of(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Writes the given attribute list, without cloning nor formatting it.
+///
+/// The first attribute is preceded by a space, and the other ones by the given separator,
+/// if any. When sorting, only the indices of the attributes are sorted.
+pub fn write_attributes<W: Write>(
+    writer: &mut W,
+    attributes: &[(XMLStr, XMLStr)],
+    sort: bool,
+    separator: Option<&[u8]>,
+) -> io::Result<()> {
+    if sort && attributes.len() > 1 {
+        let mut order: Vec<usize> = (0..attributes.len()).collect();
+        order.sort_unstable_by(|&a, &b| attributes[a].cmp(&attributes[b]));
+
+        for (position, index) in order.into_iter().enumerate() {
+            let separator = separator.filter(|_| position > 0);
+            write_attribute(writer, separator, &attributes[index])?;
+        }
+    } else {
+        for (position, attribute) in attributes.iter().enumerate() {
+            let separator = separator.filter(|_| position > 0);
+            write_attribute(writer, separator, attribute)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the given attribute, preceded by the given separator or a space.
+fn write_attribute<W: Write>(
+    writer: &mut W,
+    separator: Option<&[u8]>,
+    (key, value): &(XMLStr, XMLStr),
+) -> io::Result<()> {
+    match separator {
+        Some(separator) => writer.write_all(separator)?,
+        None => writer.write_all(b" ")?,
+    }
+    writer.write_all(key.as_bytes())?;
+    writer.write_all(b"=\"")?;
+    writer.write_all(value.as_bytes())?;
+    writer.write_all(b"\"")
+}
+
+/// Writer counting the bytes written to it, without storing them.
+#[derive(Default)]
+pub struct LengthWriter(pub usize);
+
+impl Write for LengthWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer measuring a single line, failing once it breaks or exceeds the given number of
+/// characters left.
+pub struct WidthWriter(pub usize);
+
+impl Write for WidthWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            if matches!(byte, b'\n' | b'\r') {
+                return Err(io::ErrorKind::InvalidData.into());
+            }
+            // Characters are counted by their first byte
+            if byte & 0xC0 != 0x80 {
+                self.0 = self.0.checked_sub(1).ok_or(io::ErrorKind::WriteZero)?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Incremental renderer of a whole document, filling buffers of encoded bytes.
+pub struct DocumentRenderer<'a> {
+    /// The encoding the document is written in.
+    encoding: XMLEncoding,
+
+    /// Everything preceding the root element, not written yet.
+    prolog: Option<Vec<u8>>,
+
+    /// The renderer of the root element.
+    root: Option<Renderer<'a>>,
+
+    /// Everything following the root element, not written yet.
+    epilog: Option<String>,
+}
+
+impl<'a> DocumentRenderer<'a> {
+    /// Instantiates a new `DocumentRenderer` from the given document parts.
+    pub const fn new(
+        encoding: XMLEncoding,
+        prolog: Vec<u8>,
+        root: Option<Renderer<'a>>,
+        epilog: Option<String>,
+    ) -> Self {
+        Self {
+            encoding,
+            prolog: Some(prolog),
+            root,
+            epilog,
+        }
+    }
+
+    /// Renders the document into the given buffer, until it holds at least `size` bytes.
+    ///
+    /// At least one step of the root element is rendered, so that rendering always
+    /// progresses whatever the size is.
+    ///
+    /// Returns whether some content remains to be rendered.
+    pub fn fill(&mut self, buffer: &mut Vec<u8>, size: usize) -> Result<bool> {
+        let mut writer = EncodingWriter::new(buffer, self.encoding);
+
+        if let Some(prolog) = self.prolog.take() {
+            writer.write_all(&prolog)?;
+        }
+
+        if let Some(root) = &mut self.root {
+            loop {
+                if !root.step(&mut writer)? {
+                    self.root = None;
+                    break;
+                }
+                if writer.get_ref().len() >= size {
+                    break;
+                }
+            }
+            if self.root.is_some() {
+                return Ok(true);
+            }
+        }
+
+        if let Some(epilog) = self.epilog.take() {
+            writer.write_all(epilog.as_bytes())?;
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{XMLAttributeWrapping, XMLLineEnding, XMLStr};
+
+/// Spaces borrowed by the indentation units of up to 32 spaces.
+const SPACES: &str = "                                ";
+
+/// Tabulations borrowed by the indentation units of up to 32 tabulations.
+const TABS: &str = "\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t";
+
+/// Options applied while rendering elements.
+///
+/// They can be stored and shared between renderings, and are produced by `XMLBuilder`
+/// for the documents it builds. The default options are the `pretty` ones.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderOptions {
+    /// Whether attributes are sorted, unless set on the element itself.
+    ///
+    /// Defaults to `false`.
+    pub(crate) sort_attributes: bool,
+
+    /// Whether elements are indented.
+    ///
+    /// Defaults to `true`.
+    pub(crate) indent: bool,
+
+    /// The string written once per indentation level.
+    ///
+    /// Defaults to a tabulation.
+    pub(crate) indent_unit: XMLStr,
+
+    /// Whether lines are broken after each element.
+    ///
+    /// Defaults to `true`.
+    pub(crate) break_lines: bool,
+
+    /// The line ending written when breaking lines.
+    ///
+    /// Defaults to `XMLLineEnding::LF`.
+    pub(crate) line_ending: XMLLineEnding,
+
+    /// Whether empty elements are written with an end tag.
+    ///
+    /// Defaults to `false`.
+    pub(crate) expand_empty_tags: bool,
+
+    /// The width of the lines elements are kept on when they fit, if any.
+    ///
+    /// Defaults to `None`.
+    pub(crate) inline_width: Option<usize>,
+
+    /// The width start tags are wrapped beyond, if any.
+    ///
+    /// Defaults to `None`.
+    pub(crate) max_line_width: Option<usize>,
+
+    /// The layout of the attributes of wrapped start tags.
+    ///
+    /// Defaults to `XMLAttributeWrapping::Aligned`.
+    pub(crate) attribute_wrapping: XMLAttributeWrapping,
+
+    /// The width text content is wrapped beyond, if any.
+    ///
+    /// Defaults to `None`.
+    pub(crate) text_width: Option<usize>,
+
+    /// Whether multi-line text content is re-indented relative to its element.
+    ///
+    /// Defaults to `false`.
+    pub(crate) indent_text: bool,
+
+    /// The maximum depth of the rendered elements, if any.
+    ///
+    /// Defaults to `None`.
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self::pretty()
+    }
+}
+
+impl RenderOptions {
+    /// Builds new `RenderOptions`, which are the `pretty` ones.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds new `RenderOptions` laying out each element on its own indented line.
+    #[must_use]
+    pub const fn pretty() -> Self {
+        Self::from_flags(false, true, true, false)
+    }
+
+    /// Builds new `RenderOptions` adding no whitespace between elements.
+    #[must_use]
+    pub const fn compact() -> Self {
+        Self::from_flags(false, false, false, false)
+    }
+
+    /// Builds new `RenderOptions` close to Canonical XML, adding no whitespace between
+    /// elements, sorting attributes and expanding empty elements, so that equivalent
+    /// trees are rendered identically.
+    #[must_use]
+    pub const fn canonical() -> Self {
+        Self::from_flags(true, false, false, true)
+    }
+
+    /// Sets whether attributes are sorted, unless set on the element itself.
+    #[must_use]
+    pub const fn sort_attributes(mut self, sort_attributes: bool) -> Self {
+        self.sort_attributes = sort_attributes;
+
+        self
+    }
+
+    /// Sets whether elements are indented.
+    #[must_use]
+    pub const fn indent(mut self, indent: bool) -> Self {
+        self.indent = indent;
+
+        self
+    }
+
+    /// Sets the indentation to the given number of spaces per level.
+    ///
+    /// Indentation itself is enabled by `indent`.
+    #[must_use]
+    pub fn indent_spaces(self, count: usize) -> Self {
+        self.indent_string(repeat(SPACES, ' ', count))
+    }
+
+    /// Sets the indentation to the given number of tabulations per level.
+    ///
+    /// Indentation itself is enabled by `indent`.
+    #[must_use]
+    pub fn indent_tabs(self, count: usize) -> Self {
+        self.indent_string(repeat(TABS, '\t', count))
+    }
+
+    /// Sets the string written once per indentation level.
+    ///
+    /// Indentation itself is enabled by `indent`.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The indentation of a single level, as a static, shared or owned string.
+    #[must_use]
+    pub fn indent_string<S: Into<XMLStr>>(mut self, unit: S) -> Self {
+        self.indent_unit = unit.into();
+
+        self
+    }
+
+    /// Sets whether lines are broken after each element.
+    #[must_use]
+    pub const fn break_lines(mut self, break_lines: bool) -> Self {
+        self.break_lines = break_lines;
+
+        self
+    }
+
+    /// Sets the line ending written when breaking lines.
+    #[must_use]
+    pub const fn line_ending(mut self, line_ending: XMLLineEnding) -> Self {
+        self.line_ending = line_ending;
+
+        self
+    }
+
+    /// Sets whether empty elements are written with an end tag.
+    #[must_use]
+    pub const fn expand_empty_tags(mut self, expand_empty_tags: bool) -> Self {
+        self.expand_empty_tags = expand_empty_tags;
+
+        self
+    }
+
+    /// Keeps each element on a single line, children included, when it fits within the
+    /// given width along with its indentation.
+    ///
+    /// Widths are counted in characters. Elements laid out on several lines still write
+    /// their text content on the same line as their tags, without adding whitespace,
+    /// unless it is wrapped or re-indented. Elements streamed event by event by an
+    /// `XMLWriter` are never kept on a single line.
+    #[must_use]
+    pub const fn inline_width(mut self, width: usize) -> Self {
+        self.inline_width = Some(width);
+
+        self
+    }
+
+    /// Sets the max line width, beyond which the attributes of start tags are placed one
+    /// per line.
+    ///
+    /// Widths are counted in characters, indentation included. Only start tags laid out
+    /// on their own lines are wrapped.
+    #[must_use]
+    pub const fn max_line_width(mut self, width: usize) -> Self {
+        self.max_line_width = Some(width);
+
+        self
+    }
+
+    /// Sets the layout of the attributes of start tags exceeding the max line width.
+    #[must_use]
+    pub const fn attribute_wrapping(mut self, attribute_wrapping: XMLAttributeWrapping) -> Self {
+        self.attribute_wrapping = attribute_wrapping;
+
+        self
+    }
+
+    /// Sets the width beyond which text content is word-wrapped.
+    ///
+    /// Widths are counted in characters, indentation included. An element whose text
+    /// does not fit on the line of its tags, or spans several lines, is laid out with its
+    /// text on indented lines between its tags. Words longer than the width are kept
+    /// whole, and text within `xml:space="preserve"` is written as is.
+    #[must_use]
+    pub const fn text_width(mut self, width: usize) -> Self {
+        self.text_width = Some(width);
+
+        self
+    }
+
+    /// Sets whether multi-line text content is re-indented relative to its element.
+    ///
+    /// The lines of such a text are written between the tags of the element, one level
+    /// deeper, keeping their indentation relative to each other. Surrounding blank lines
+    /// are dropped, and text within `xml:space="preserve"` is written as is.
+    #[must_use]
+    pub const fn indent_text(mut self, indent_text: bool) -> Self {
+        self.indent_text = indent_text;
+
+        self
+    }
+
+    /// Sets the maximum depth of the rendered elements.
+    ///
+    /// Rendering a deeper element raises `XMLError::DepthLimitExceeded` instead of
+    /// writing it.
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+
+        self
+    }
+
+    /// Internal method building `RenderOptions` from the given flags, without depth limit.
+    pub(crate) const fn from_flags(
+        sort_attributes: bool,
+        indent: bool,
+        break_lines: bool,
+        expand_empty_tags: bool,
+    ) -> Self {
+        Self {
+            sort_attributes,
+            indent,
+            indent_unit: XMLStr::from_static("\t"),
+            break_lines,
+            line_ending: XMLLineEnding::LF,
+            expand_empty_tags,
+            inline_width: None,
+            max_line_width: None,
+            attribute_wrapping: XMLAttributeWrapping::Aligned,
+            text_width: None,
+            indent_text: false,
+            max_depth: None,
+        }
+    }
+}
+
+/// Returns the given character repeated the given number of times, borrowing it from the
+/// given static string when it is long enough.
+fn repeat(repeated: &'static str, character: char, count: usize) -> XMLStr {
+    match repeated.get(..count) {
+        Some(unit) => XMLStr::from_static(unit),
+        None => XMLStr::shared(&character.to_string().repeat(count)),
+    }
+}
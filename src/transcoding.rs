@@ -0,0 +1,390 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::{Result, XMLEncoding, XMLError};
+
+/// Maximum number of bytes read while looking for the encoding of the XML declaration.
+const MAX_DECLARATION_LENGTH: usize = 1024;
+
+/// Reader adapter detecting the encoding of a document and decoding it into UTF-8.
+///
+/// UTF-8 documents are passed through without any copy once the encoding is detected.
+pub struct DecodingReader<R: BufRead> {
+    /// The source the document is read from.
+    inner: R,
+
+    /// The detected encoding, set once the start of the document has been read.
+    encoding: Option<XMLEncoding>,
+
+    /// Decoded UTF-8 bytes not consumed yet.
+    decoded: Vec<u8>,
+
+    /// Position of the first unconsumed byte in `decoded`.
+    position: usize,
+
+    /// Undecoded bytes, either read during detection or ending with an incomplete character.
+    pending: Vec<u8>,
+}
+
+impl<R: BufRead> DecodingReader<R> {
+    /// Instantiates a new `DecodingReader` reading from the given source.
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            encoding: None,
+            decoded: Vec::new(),
+            position: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Consumes the reader, returning the underlying source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Returns the detected encoding, if the start of the document has been read.
+    pub const fn encoding(&self) -> Option<XMLEncoding> {
+        self.encoding
+    }
+
+    /// Detects the encoding of the document from its byte order mark, or from the
+    /// encoding declared in its XML declaration.
+    pub fn detect(&mut self) -> Result<XMLEncoding> {
+        if let Some(encoding) = self.encoding {
+            return Ok(encoding);
+        }
+
+        self.read_pending(|pending| pending.len() >= 4)?;
+        let encoding = match self.pending.as_slice() {
+            [0xEF, 0xBB, 0xBF, ..] => Some(XMLEncoding::UTF8),
+            [0xFF, 0xFE, ..] | [b'<', 0, b'?', 0, ..] => Some(XMLEncoding::UTF16LE),
+            [0xFE, 0xFF, ..] | [0, b'<', 0, b'?', ..] => Some(XMLEncoding::UTF16BE),
+            _ => None,
+        };
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None if self.pending.starts_with(b"<?xml") => {
+                self.read_pending(|pending| {
+                    pending.len() > MAX_DECLARATION_LENGTH || pending.windows(2).any(|w| w == b"?>")
+                })?;
+                match declared_encoding(&self.pending) {
+                    Some(label) => match XMLEncoding::from_label(&label) {
+                        Some(XMLEncoding::UTF16LE | XMLEncoding::UTF16BE) => {
+                            return Err(XMLError::ParseError(format!(
+                                "document declared as `{label}` is not encoded in UTF-16"
+                            )));
+                        }
+                        Some(encoding) => encoding,
+                        None => {
+                            return Err(XMLError::ParseError(format!(
+                                "unsupported encoding `{label}`"
+                            )));
+                        }
+                    },
+                    None => XMLEncoding::UTF8,
+                }
+            }
+            None => XMLEncoding::UTF8,
+        };
+
+        self.encoding = Some(encoding);
+        if encoding == XMLEncoding::UTF8 {
+            std::mem::swap(&mut self.decoded, &mut self.pending);
+        } else {
+            let pending = std::mem::take(&mut self.pending);
+            self.decode(&pending, false)?;
+        }
+
+        Ok(encoding)
+    }
+
+    /// Internal method moving bytes from the source to the pending ones until the given
+    /// condition is met or the end of the document is reached.
+    fn read_pending(&mut self, done: impl Fn(&[u8]) -> bool) -> io::Result<()> {
+        while !done(&self.pending) {
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let length = chunk.len();
+            self.pending.extend_from_slice(chunk);
+            self.inner.consume(length);
+        }
+
+        Ok(())
+    }
+
+    /// Internal method decoding the given bytes, prefixed by the pending ones, into UTF-8.
+    ///
+    /// Bytes of an incomplete trailing character are kept pending, unless it is the end
+    /// of the document.
+    fn decode(&mut self, bytes: &[u8], end: bool) -> io::Result<()> {
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(bytes);
+
+        let mut output = String::with_capacity(input.len());
+        match self.encoding {
+            Some(encoding @ (XMLEncoding::UTF16LE | XMLEncoding::UTF16BE)) => {
+                let mut units: Vec<u16> = input
+                    .chunks_exact(2)
+                    .map(|pair| match encoding {
+                        XMLEncoding::UTF16LE => u16::from_le_bytes([pair[0], pair[1]]),
+                        _ => u16::from_be_bytes([pair[0], pair[1]]),
+                    })
+                    .collect();
+                let mut used = units.len() * 2;
+
+                // A high surrogate may be completed by the next chunk
+                if !end
+                    && units
+                        .last()
+                        .is_some_and(|unit| (0xD800..0xDC00).contains(unit))
+                {
+                    units.pop();
+                    used -= 2;
+                }
+                for c in char::decode_utf16(units) {
+                    output.push(c.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+                }
+
+                if end && used < input.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "incomplete UTF-16 character",
+                    ));
+                }
+                self.pending.extend_from_slice(&input[used..]);
+            }
+            Some(encoding) => output.extend(input.iter().map(|&byte| encoding.decode_byte(byte))),
+            None => unreachable!("encoding is detected before decoding"),
+        }
+
+        self.decoded.drain(..self.position);
+        self.position = 0;
+        self.decoded.extend_from_slice(output.as_bytes());
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+
+        Ok(length)
+    }
+}
+
+impl<R: BufRead> BufRead for DecodingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            None => self
+                .detect()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        };
+
+        while self.position >= self.decoded.len() {
+            if encoding == XMLEncoding::UTF8 {
+                return self.inner.fill_buf();
+            }
+
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                let pending = std::mem::take(&mut self.pending);
+                if !pending.is_empty() {
+                    self.decode(&pending, true)?;
+                }
+                break;
+            }
+
+            let chunk = chunk.to_vec();
+            self.inner.consume(chunk.len());
+            self.decode(&chunk, false)?;
+        }
+
+        Ok(&self.decoded[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if self.position < self.decoded.len() {
+            self.position += amount;
+            if self.position >= self.decoded.len() {
+                self.decoded.clear();
+                self.position = 0;
+            }
+        } else {
+            self.inner.consume(amount);
+        }
+    }
+}
+
+/// The part of a document the written characters belong to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Context {
+    /// Character data, between markup.
+    Text,
+
+    /// Inside a tag or a declaration, outside of attribute values.
+    Markup,
+
+    /// Inside an attribute value delimited by the given quote.
+    Value(char),
+
+    /// Inside a comment.
+    Comment,
+
+    /// Inside a CDATA section.
+    CData,
+
+    /// Inside a processing instruction.
+    Instruction,
+}
+
+/// Writer adapter encoding the UTF-8 bytes written to it into the given encoding.
+///
+/// Characters which cannot be represented by the encoding are written as character
+/// references in text and attribute values. Elsewhere, such as in names, comments or
+/// CDATA sections, references would not be expanded, so that writing them fails.
+pub struct EncodingWriter<W: Write> {
+    /// The writer the encoded bytes are written to.
+    inner: W,
+
+    /// The encoding to write in.
+    encoding: XMLEncoding,
+
+    /// Bytes of an incomplete UTF-8 character, completed by the next write.
+    pending: Vec<u8>,
+
+    /// The part of the document the next character belongs to.
+    context: Context,
+
+    /// The last characters written, as ASCII bytes, used to detect markup delimiters.
+    recent: [u8; 9],
+}
+
+impl<W: Write> EncodingWriter<W> {
+    /// Instantiates a new `EncodingWriter` writing into the given writer.
+    pub const fn new(inner: W, encoding: XMLEncoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            pending: Vec::new(),
+            context: Context::Text,
+            recent: [0; 9],
+        }
+    }
+
+    /// Internal method updating the context of the next character after the given one.
+    fn track(&mut self, c: char) {
+        self.recent.rotate_left(1);
+        self.recent[8] = u8::try_from(c).ok().filter(u8::is_ascii).unwrap_or(0);
+
+        let ends_with = |delimiter: &[u8]| self.recent.ends_with(delimiter);
+        self.context = match (self.context, c) {
+            (Context::Text, '<') => Context::Markup,
+            (Context::Markup, _) if ends_with(b"<?") => Context::Instruction,
+            (Context::Markup, _) if ends_with(b"<!--") => Context::Comment,
+            (Context::Markup, _) if ends_with(b"<![CDATA[") => Context::CData,
+            (Context::Markup, '"' | '\'') => Context::Value(c),
+            (Context::Markup, '>') => Context::Text,
+            (Context::Value(quote), _) if c == quote => Context::Markup,
+            (Context::Comment, '>') if ends_with(b"-->") => Context::Text,
+            (Context::CData, '>') if ends_with(b"]]>") => Context::Text,
+            (Context::Instruction, '>') if ends_with(b"?>") => Context::Text,
+            (context, _) => context,
+        };
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the writer, returning the underlying one.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for EncodingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoding == XMLEncoding::UTF8 {
+            return self.inner.write(buf);
+        }
+
+        self.pending.extend_from_slice(buf);
+        let input = std::mem::take(&mut self.pending);
+        let valid = match std::str::from_utf8(&input) {
+            Ok(valid) => valid,
+            Err(e) if e.error_len().is_none() => {
+                self.pending = input[e.valid_up_to()..].to_vec();
+                std::str::from_utf8(&input[..e.valid_up_to()]).unwrap_or_default()
+            }
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+
+        let mut output = Vec::with_capacity(valid.len() * 2);
+        for c in valid.chars() {
+            match self.encoding {
+                XMLEncoding::UTF16LE | XMLEncoding::UTF16BE => {
+                    for unit in c.encode_utf16(&mut [0; 2]) {
+                        output.extend_from_slice(&match self.encoding {
+                            XMLEncoding::UTF16LE => unit.to_le_bytes(),
+                            _ => unit.to_be_bytes(),
+                        });
+                    }
+                }
+                encoding => {
+                    match encoding.encode_byte(c) {
+                        Some(byte) => output.push(byte),
+                        None if matches!(self.context, Context::Text | Context::Value(_)) => {
+                            write!(output, "&#{};", u32::from(c))?;
+                        }
+                        None => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "character `{c}` cannot be written in {encoding} outside of \
+                                     text and attribute values"
+                                ),
+                            ));
+                        }
+                    }
+                    self.track(c);
+                }
+            }
+        }
+        self.inner.write_all(&output)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns the value of the `encoding` pseudo-attribute of the given XML declaration, if any.
+fn declared_encoding(declaration: &[u8]) -> Option<String> {
+    let end = declaration
+        .windows(2)
+        .position(|w| w == b"?>")
+        .unwrap_or(declaration.len());
+    let declaration = String::from_utf8_lossy(&declaration[..end]);
+
+    let value = declaration
+        .split_once("encoding")?
+        .1
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let value = &value[1..];
+
+    Some(value[..value.find(quote)?].to_owned())
+}
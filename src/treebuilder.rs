@@ -0,0 +1,94 @@
+use crate::utils::is_whitespace;
+use crate::{Result, XMLElement, XMLError, XMLEvent, XMLLimits, escape_str};
+
+/// Builder assembling `XMLElement` trees from the events yielded by an `XMLReader`.
+///
+/// Comments, processing instructions and whitespaces between elements are discarded.
+pub struct TreeBuilder {
+    /// The currently opened elements, from the outermost to the deepest one.
+    elements: Vec<XMLElement>,
+
+    /// Unescaped text read since the last start or end of element.
+    text: String,
+
+    /// Maximum length in bytes of the whole text of an element.
+    max_text_length: usize,
+}
+
+impl TreeBuilder {
+    /// Instantiates a new empty `TreeBuilder` enforcing the text length of the given limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The `XMLLimits` the document is read with.
+    pub const fn new(limits: &XMLLimits) -> Self {
+        Self {
+            elements: Vec::new(),
+            text: String::new(),
+            max_text_length: limits.max_text_length,
+        }
+    }
+
+    /// Pushes the given event into the tree being built.
+    ///
+    /// Returns the outermost element once it has been closed.
+    ///
+    /// Raises `XMLError` if an element mixes text and child elements, or if its text split
+    /// across several events is longer than allowed.
+    pub fn push(&mut self, event: XMLEvent) -> Result<Option<XMLElement>> {
+        match event {
+            XMLEvent::StartElement { name, attributes } => {
+                check_no_mixed_content(&self.text)?;
+                self.text.clear();
+
+                let mut element = XMLElement::from_name(name);
+                for (key, value) in &attributes {
+                    element.add_attribute(key, value);
+                }
+                self.elements.push(element);
+            }
+            XMLEvent::EndElement { .. } => {
+                let Some(mut element) = self.elements.pop() else {
+                    unreachable!("XMLReader ensures tags are balanced")
+                };
+
+                if element.children().is_empty() {
+                    if !self.text.is_empty() {
+                        element.add_text(escape_str(&self.text))?;
+                    }
+                } else {
+                    check_no_mixed_content(&self.text)?;
+                }
+                self.text.clear();
+
+                match self.elements.last_mut() {
+                    Some(parent) => parent.add_child(element)?,
+                    None => return Ok(Some(element)),
+                }
+            }
+            XMLEvent::Text(content) | XMLEvent::CData(content) => {
+                if self.text.len().saturating_add(content.len()) > self.max_text_length {
+                    return Err(XMLError::TextTooLong(self.max_text_length));
+                }
+                self.text.push_str(&content);
+            }
+            XMLEvent::Declaration { .. }
+            | XMLEvent::Comment(_)
+            | XMLEvent::ProcessingInstruction { .. }
+            | XMLEvent::DocumentType(_) => {}
+        }
+
+        Ok(None)
+    }
+}
+
+/// Raises `XMLError` if the given text found next to child elements is not only whitespaces.
+fn check_no_mixed_content(text: &str) -> Result<()> {
+    if is_whitespace(text) {
+        Ok(())
+    } else {
+        Err(XMLError::ParseError(
+            "mixed text and elements content is not supported".into(),
+        ))
+    }
+}
@@ -1,3 +1,5 @@
+use crate::XMLStr;
+
 pub fn escape_str(input: &str) -> String {
     input
         .to_owned()
@@ -7,3 +9,60 @@ pub fn escape_str(input: &str) -> String {
         .replace('<', "&lt;")
         .replace('>', "&gt;")
 }
+
+/// Escapes the given string, keeping it as is when no character needs to be escaped.
+pub fn escape_xml_str(input: XMLStr) -> XMLStr {
+    if input.contains(['&', '"', '\'', '<', '>']) {
+        escape_str(&input).into()
+    } else {
+        input
+    }
+}
+
+pub fn predefined_entity(entity: &str) -> std::result::Result<Option<char>, String> {
+    let c = match entity {
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        "lt" => '<',
+        "gt" => '>',
+        _ => {
+            let code = match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => match entity.strip_prefix('#') {
+                    Some(decimal) => decimal.parse(),
+                    None => return Ok(None),
+                },
+            };
+            code.ok()
+                .and_then(char::from_u32)
+                .filter(|&c| is_xml_char(c))
+                .ok_or_else(|| format!("invalid character reference `&{entity};`"))?
+        }
+    };
+
+    Ok(Some(c))
+}
+
+/// Returns whether the given character matches the `Char` production of XML 1.0.
+pub const fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}')
+        || c >= '\u{10000}'
+}
+
+pub const fn is_whitespace_char(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+pub fn is_whitespace(input: &str) -> bool {
+    input.chars().all(is_whitespace_char)
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':' || !c.is_ascii())
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.') || !c.is_ascii())
+}
@@ -1,6 +1,14 @@
-use std::io::Write;
+use std::io::{BufRead, Write};
 
-use crate::{Result, XMLElement, XMLVersion};
+use crate::renderer::{CHUNK_SIZE, DocumentRenderer, Elements, LengthWriter, Renderer};
+use crate::transcoding::EncodingWriter;
+use crate::treebuilder::TreeBuilder;
+use crate::utils::is_whitespace;
+use crate::xmltrivia::XMLTrivia;
+use crate::{
+    RenderOptions, Result, XMLArena, XMLBuilder, XMLElement, XMLEncoding, XMLError, XMLEvent,
+    XMLLimits, XMLReader, XMLVersion,
+};
 
 /// Structure representing a XML document.
 /// It must be used to create a XML document.
@@ -24,28 +32,32 @@ pub struct XML {
     /// Defaults to `None`
     standalone: Option<bool>,
 
-    /// Whether the XML attributes should be sorted or not.
+    /// The options applied while rendering the document.
     ///
-    /// Defaults to `false`.
-    sort_attributes: bool,
+    /// Defaults to `RenderOptions::pretty()`.
+    pub(crate) options: RenderOptions,
 
-    /// Whether we want to indentate the document.
+    /// The encoding the document is written in.
     ///
-    /// Defaults to `true`.
-    indent: bool,
+    /// Defaults to `UTF-8`.
+    pub(crate) output_encoding: XMLEncoding,
 
-    /// Whether we want to break lines or not.
+    /// The minimum number of children of an element for them to be rendered concurrently.
     ///
-    /// Defaults to `true`.
-    break_lines: bool,
-
-    /// Whether we want to expand empty tags or not.
-    ///
-    /// Defaults to `false`.
-    expand_empty_tags: bool,
+    /// Defaults to `None`.
+    #[cfg(feature = "rayon")]
+    parallel: Option<usize>,
 
     /// The root XML element.
     root: Option<XMLElement>,
+
+    /// Raw content preceding the root element, only set by lossless parsing.
+    ///
+    /// When set, it is written instead of the generated XML declaration.
+    prolog: Option<String>,
+
+    /// Raw content following the root element, only set by lossless parsing.
+    epilog: Option<String>,
 }
 
 impl XML {
@@ -53,23 +65,227 @@ impl XML {
         version: XMLVersion,
         encoding: Option<String>,
         standalone: Option<bool>,
-        indent: bool,
-        sort_attributes: bool,
-        break_lines: bool,
-        expand_empty_tags: bool,
+        options: RenderOptions,
     ) -> Self {
         Self {
             version,
             encoding,
             standalone,
-            indent,
-            sort_attributes,
-            break_lines,
-            expand_empty_tags,
+            options,
+            output_encoding: XMLEncoding::UTF8,
+            #[cfg(feature = "rayon")]
+            parallel: None,
             root: None,
+            prolog: None,
+            epilog: None,
         }
     }
 
+    /// Internal method setting the minimum number of children of an element for them to
+    /// be rendered concurrently.
+    #[cfg(feature = "rayon")]
+    pub(crate) const fn with_parallel(mut self, parallel: Option<usize>) -> Self {
+        self.parallel = parallel;
+
+        self
+    }
+
+    /// Internal method setting the encoding the document is written in.
+    pub(crate) const fn with_output_encoding(mut self, output_encoding: XMLEncoding) -> Self {
+        self.output_encoding = output_encoding;
+
+        self
+    }
+
+    /// Parses an XML document from the specified reader into an `XML` object tree.
+    ///
+    /// The XML declaration is reflected into the document version, encoding and standalone
+    /// attributes, while comments, processing instructions and whitespaces between elements
+    /// are discarded. Formatting options are the `XMLBuilder` default ones.
+    ///
+    /// The detected encoding of the document is kept as output encoding, so that it is
+    /// generated in the same encoding.
+    ///
+    /// Only entity expansion is limited, by `XMLLimits::trusted()`.
+    ///
+    /// Raises `XMLError` if the document is not well-formed or mixes text and elements
+    /// inside the same element.
+    ///
+    /// # Arguments
+    ///
+    /// `reader` - An object implementing `BufRead` to read the document from.
+    pub fn parse<R: BufRead>(reader: R) -> Result<Self> {
+        Self::parse_with_limits(reader, XMLLimits::trusted())
+    }
+
+    /// Parses an untrusted XML document from the specified reader into an `XML` object tree.
+    ///
+    /// Behaves like `XML::parse`, while enforcing the default `XMLLimits` to protect
+    /// against malicious documents.
+    ///
+    /// # Arguments
+    ///
+    /// `reader` - An object implementing `BufRead` to read the document from.
+    pub fn parse_untrusted<R: BufRead>(reader: R) -> Result<Self> {
+        Self::parse_with_limits(reader, XMLLimits::default())
+    }
+
+    /// Parses an XML document from the specified reader into an `XML` object tree,
+    /// enforcing the given limits.
+    ///
+    /// Behaves like `XML::parse`, raising the corresponding `XMLError` as soon as a limit
+    /// is exceeded.
+    ///
+    /// # Arguments
+    ///
+    /// `reader` - An object implementing `BufRead` to read the document from.
+    /// `limits` - The `XMLLimits` to enforce while reading.
+    pub fn parse_with_limits<R: BufRead>(reader: R, limits: XMLLimits) -> Result<Self> {
+        let mut builder = XMLBuilder::new();
+        let mut tree = TreeBuilder::new(&limits);
+        let mut root = None;
+
+        let mut reader = XMLReader::with_limits(reader, limits);
+        while let Some(event) = reader.next_event()? {
+            if let XMLEvent::Declaration {
+                version,
+                encoding,
+                standalone,
+            } = event
+            {
+                builder = apply_declaration(builder, &version, encoding, standalone)?;
+            } else if let Some(element) = tree.push(event)? {
+                root = Some(element);
+            }
+        }
+
+        let mut xml = builder
+            .output_encoding(reader.encoding().unwrap_or(XMLEncoding::UTF8))
+            .build();
+        xml.root = root;
+
+        Ok(xml)
+    }
+
+    /// Parses an XML document from the specified reader, preserving its original formatting.
+    ///
+    /// Whitespaces, attributes order and quotes, comments, character references and the
+    /// original declaration are kept, so that generating an unmodified document yields
+    /// the exact same bytes. Edited elements keep their formatting, and added ones are
+    /// laid out like their previous sibling, keeping diffs minimal.
+    ///
+    /// Mixed content is supported: text found between child elements is preserved as is,
+    /// and an element without children holds its raw content as text, unless it is only
+    /// made of whitespaces.
+    ///
+    /// # Arguments
+    ///
+    /// `reader` - An object implementing `BufRead` to read the document from.
+    pub fn parse_lossless<R: BufRead>(reader: R) -> Result<Self> {
+        let mut reader = XMLReader::new(reader);
+        reader.record_raw();
+
+        let mut builder = XMLBuilder::new();
+        let mut prolog = String::new();
+        let mut epilog = String::new();
+        // Opened elements, along with their trivia and raw content read since their last child
+        let mut elements: Vec<(XMLElement, XMLTrivia, String)> = Vec::new();
+        let mut root = None;
+
+        while let Some(event) = reader.next_event()? {
+            let raw = reader.raw();
+
+            match event {
+                XMLEvent::StartElement { name, .. } => {
+                    let tag = match elements.last_mut() {
+                        Some((_, trivia, content)) => {
+                            trivia.gaps.push(std::mem::take(content));
+                            raw
+                        }
+                        None => {
+                            let start = raw.find('<').unwrap_or_default();
+                            prolog.push_str(&raw[..start]);
+                            &raw[start..]
+                        }
+                    };
+
+                    let (trivia, attributes) = XMLTrivia::from_start_tag(&tag[1..tag.len() - 1]);
+                    let mut element = XMLElement::from_name(name);
+                    for (key, value) in attributes {
+                        element.add_escaped_attribute(key, value);
+                    }
+                    elements.push((element, trivia, String::new()));
+                }
+                XMLEvent::EndElement { name } => {
+                    let Some((mut element, mut trivia, content)) = elements.pop() else {
+                        unreachable!("XMLReader ensures tags are balanced")
+                    };
+
+                    // Whitespaces alone are kept as a gap, so that children can still be added
+                    if element.children().is_empty() && !is_whitespace(&content) {
+                        element.add_text(content)?;
+                        trivia.gaps.push(String::new());
+                    } else {
+                        trivia.gaps.push(content);
+                    }
+                    // Self-closing tags have no raw end tag
+                    if let Some(end) = raw.get(name.len() + 2..raw.len().saturating_sub(1)) {
+                        trivia.end_tag_end = end.to_owned();
+                    }
+                    element.set_trivia(trivia);
+
+                    match elements.last_mut() {
+                        Some((parent, _, _)) => parent.add_child(element)?,
+                        None => root = Some(element),
+                    }
+                }
+                event => {
+                    if let XMLEvent::Declaration {
+                        version,
+                        encoding,
+                        standalone,
+                    } = event
+                    {
+                        builder = apply_declaration(builder, &version, encoding, standalone)?;
+                    }
+
+                    match elements.last_mut() {
+                        Some((_, _, content)) => content.push_str(raw),
+                        None if root.is_none() => prolog.push_str(raw),
+                        None => epilog.push_str(raw),
+                    }
+                }
+            }
+        }
+        epilog.push_str(reader.raw());
+
+        let mut xml = builder
+            .output_encoding(reader.encoding().unwrap_or(XMLEncoding::UTF8))
+            .build();
+        xml.root = root;
+        xml.prolog = Some(prolog);
+        xml.epilog = Some(epilog);
+
+        Ok(xml)
+    }
+
+    /// Returns the encoding the document is written in.
+    #[must_use]
+    pub const fn output_encoding(&self) -> XMLEncoding {
+        self.output_encoding
+    }
+
+    /// Returns the XML document root element, if any.
+    #[must_use]
+    pub const fn root_element(&self) -> Option<&XMLElement> {
+        self.root.as_ref()
+    }
+
+    /// Returns the mutable XML document root element, if any.
+    pub const fn root_element_mut(&mut self) -> Option<&mut XMLElement> {
+        self.root.as_mut()
+    }
+
     /// Sets the XML document root element.
     ///
     /// # Arguments
@@ -82,13 +298,141 @@ impl XML {
     /// Generates an XML document into the specified `Writer`.
     ///
     /// Consumes the XML object.
-    pub fn generate<W: Write>(self, mut writer: W) -> Result<()> {
+    pub fn generate<W: Write>(self, writer: W) -> Result<()> {
+        write_document(self.into_renderer(None)?, writer)
+    }
+
+    /// Generates an XML document into the specified `Writer`, the root element of the
+    /// given `XMLArena` being rendered instead of the document one.
+    ///
+    /// The prolog, encoding and options of the document apply as with `generate`, without
+    /// copying the arena into `XMLElement`s.
+    ///
+    /// # Arguments
+    ///
+    /// * `arena` - The `XMLArena` holding the root element to render.
+    /// * `writer` - An object to generate the document to.
+    pub fn generate_arena<W: Write>(&self, arena: &XMLArena, writer: W) -> Result<()> {
+        let mut prolog = Vec::new();
+        self.write_prolog(&mut prolog)?;
+
+        let root = arena
+            .root()
+            .map(|root| Renderer::arena(arena, root.0, self.options.clone()));
+        let renderer =
+            DocumentRenderer::new(self.output_encoding, prolog, root, self.epilog.clone());
+
+        write_document(renderer, writer)
+    }
+
+    /// Computes the exact number of bytes `generate` would write, without writing anything.
+    ///
+    /// Raises `XMLError` if an element has lazy children, which cannot be measured without
+    /// being consumed.
+    pub fn rendered_len(&self) -> Result<usize> {
+        let mut writer = EncodingWriter::new(LengthWriter::default(), self.output_encoding);
+
+        self.write_prolog(&mut writer)?;
+        if let Some(root) = &self.root {
+            Renderer::new(root, 0, self.options.clone())
+                .measuring()
+                .render(&mut writer)?;
+        }
+        if let Some(epilog) = &self.epilog {
+            writer.write_all(epilog.as_bytes())?;
+        }
+
+        Ok(writer.into_inner().0)
+    }
+
+    /// Computes the exact number of bytes `generate_arena` would write, without writing anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `arena` - The `XMLArena` holding the root element to render.
+    pub fn rendered_len_arena(&self, arena: &XMLArena) -> Result<usize> {
+        let mut writer = EncodingWriter::new(LengthWriter::default(), self.output_encoding);
+
+        self.write_prolog(&mut writer)?;
+        if let Some(root) = arena.root() {
+            Renderer::arena(arena, root.0, self.options.clone()).render(&mut writer)?;
+        }
+        if let Some(epilog) = &self.epilog {
+            writer.write_all(epilog.as_bytes())?;
+        }
+
+        Ok(writer.into_inner().0)
+    }
+
+    /// Internal method instantiating an incremental renderer of the document, consuming it.
+    ///
+    /// The given children are rendered after the root element ones.
+    pub(crate) fn into_renderer(
+        self,
+        appended: Option<Elements<'static>>,
+    ) -> Result<DocumentRenderer<'static>> {
+        let mut prolog = Vec::new();
+        self.write_prolog(&mut prolog)?;
+
+        let options = self.options;
+        let root = self
+            .root
+            .map(|root| Renderer::owned(root, appended, options));
+        #[cfg(feature = "rayon")]
+        let root = root.map(|root| root.with_parallel(self.parallel));
+
+        Ok(DocumentRenderer::new(
+            self.output_encoding,
+            prolog,
+            root,
+            self.epilog,
+        ))
+    }
+
+    /// Returns the options applied while rendering the document.
+    #[must_use]
+    pub const fn render_options(&self) -> &RenderOptions {
+        &self.options
+    }
+
+    /// Sets the options applied while rendering the document.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `RenderOptions` to apply.
+    pub fn set_render_options(&mut self, options: RenderOptions) {
+        self.options = options;
+    }
+
+    /// Internal method writing everything preceding the root element.
+    pub(crate) fn write_prolog<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if let Some(prolog) = &self.prolog {
+            write!(writer, "{prolog}")?;
+        } else {
+            // UTF-16 documents must start with a byte order mark
+            if matches!(
+                self.output_encoding,
+                XMLEncoding::UTF16LE | XMLEncoding::UTF16BE
+            ) {
+                write!(writer, "\u{feff}")?;
+            }
+            self.write_declaration(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing the generated XML declaration into the specified `Writer`.
+    fn write_declaration<W: Write>(&self, writer: &mut W) -> Result<()> {
         write!(
             writer,
             r#"<?xml version="{}"{encoding}{standalone}?>"#,
             self.version,
             encoding = self
                 .encoding
+                .clone()
+                .or_else(|| (self.output_encoding != XMLEncoding::UTF8)
+                    .then(|| self.output_encoding.to_string()))
                 .map_or_else(String::default, |encoding| format!(
                     " encoding=\"{encoding}\""
                 )),
@@ -101,21 +445,52 @@ impl XML {
             }
         )?;
 
-        if self.break_lines {
-            writeln!(writer)?;
-        }
-
-        // And then XML elements if present...
-        if let Some(elem) = &self.root {
-            elem.render(
-                &mut writer,
-                self.sort_attributes,
-                self.indent,
-                self.break_lines,
-                self.expand_empty_tags,
-            )?;
+        if self.options.break_lines {
+            writer.write_all(self.options.line_ending.as_str().as_bytes())?;
         }
 
         Ok(())
     }
 }
+
+/// Writes the whole document rendered by the given renderer into the specified `Writer`.
+fn write_document<W: Write>(mut renderer: DocumentRenderer<'_>, mut writer: W) -> Result<()> {
+    // Tags are coalesced into chunks, so that small writes do not reach the writer
+    let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+    loop {
+        let remaining = renderer.fill(&mut buffer, CHUNK_SIZE)?;
+        writer.write_all(&buffer)?;
+        buffer.clear();
+
+        if !remaining {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the given XML declaration attributes to the given `XMLBuilder`.
+fn apply_declaration(
+    builder: XMLBuilder,
+    version: &str,
+    encoding: Option<String>,
+    standalone: Option<bool>,
+) -> Result<XMLBuilder> {
+    let builder = builder
+        .version(match version {
+            "1.0" => XMLVersion::XML1_0,
+            "1.1" => XMLVersion::XML1_1,
+            _ => {
+                return Err(XMLError::ParseError(format!(
+                    "unsupported XML version `{version}`"
+                )));
+            }
+        })
+        .standalone(standalone);
+
+    Ok(match encoding {
+        Some(encoding) => builder.encoding(encoding),
+        None => builder,
+    })
+}
@@ -0,0 +1,455 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use crate::renderer::Renderer;
+use crate::utils::escape_xml_str;
+use crate::{RenderOptions, Result, XMLElement, XMLElementContent, XMLError, XMLStr, escape_str};
+
+/// Identifier of a node stored in a `XMLArena`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XMLNodeId(pub(crate) usize);
+
+/// Internal structure representing an element stored in a `XMLArena`.
+pub(crate) struct Node {
+    /// The name of the XML element.
+    pub(crate) name: XMLStr,
+
+    /// A list of tuple representing (key, value) attributes.
+    pub(crate) attributes: Vec<(XMLStr, XMLStr)>,
+
+    /// A boolean representing whether we want attributes to be sorted.
+    pub(crate) sort_attributes: Option<bool>,
+
+    /// Whether this element and its descendants are indented.
+    pub(crate) indent: Option<bool>,
+
+    /// Whether lines are broken after the tags of this element and its descendants.
+    pub(crate) break_lines: Option<bool>,
+
+    /// Whether this element and its empty descendants are written with an end tag.
+    pub(crate) expand_empty_tags: Option<bool>,
+
+    /// The text content of this XML element, if any.
+    pub(crate) text: Option<String>,
+
+    /// The parent of this XML element, if attached.
+    parent: Option<usize>,
+
+    /// The first child of this XML element.
+    pub(crate) first_child: Option<usize>,
+
+    /// The last child of this XML element.
+    last_child: Option<usize>,
+
+    /// The next sibling of this XML element.
+    pub(crate) next_sibling: Option<usize>,
+}
+
+/// Structure representing a tree of XML elements stored contiguously.
+///
+/// Elements are referenced by `XMLNodeId` identifiers instead of owning their children,
+/// which saves many small allocations and improves locality for trees of millions of
+/// elements. It converts to and from `XMLElement` trees, and its root element is generated
+/// as the root of a document by `XML::generate_arena`.
+///
+/// Lazy children and render caches are not supported, and documents whose root is stored
+/// in an arena cannot be turned into a byte stream, which needs an owned `XMLElement` root.
+///
+/// Methods panic if given an identifier that was not returned by the same arena.
+#[derive(Default)]
+pub struct XMLArena {
+    /// The elements of the tree, in creation order.
+    nodes: Vec<Node>,
+
+    /// The root element of the tree, if any.
+    root: Option<usize>,
+}
+
+impl XMLArena {
+    /// Instantiates a new empty `XMLArena` object.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Instantiates a new empty `XMLArena` object, able to store the given number of
+    /// elements without reallocating.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of elements to allocate room for.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            root: None,
+        }
+    }
+
+    /// Returns the number of elements stored in the `XMLArena`, attached or not.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the `XMLArena` does not store any element.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a new detached element to the `XMLArena`, returning its identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the XML element.
+    pub fn new_element(&mut self, name: &str) -> XMLNodeId {
+        self.new_element_from_name(name.to_owned())
+    }
+
+    /// Adds a new detached element to the `XMLArena` from a `XMLStr` name, returning its
+    /// identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the XML element, as a static, shared or owned string.
+    pub fn new_element_from_name<N: Into<XMLStr>>(&mut self, name: N) -> XMLNodeId {
+        self.nodes.push(Node {
+            name: name.into(),
+            attributes: Vec::new(),
+            sort_attributes: None,
+            indent: None,
+            break_lines: None,
+            expand_empty_tags: None,
+            text: None,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            next_sibling: None,
+        });
+
+        XMLNodeId(self.nodes.len() - 1)
+    }
+
+    /// Returns the root element of the `XMLArena`, if set.
+    #[must_use]
+    pub fn root(&self) -> Option<XMLNodeId> {
+        self.root.map(XMLNodeId)
+    }
+
+    /// Sets the root element of the `XMLArena`.
+    ///
+    /// Raises `XMLError` if the element is a child of another element.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the new root element.
+    pub fn set_root(&mut self, node: XMLNodeId) -> Result<()> {
+        if self.nodes[node.0].parent.is_some() {
+            return Err(XMLError::InsertError(
+                "Cannot set an attached element as root".into(),
+            ));
+        }
+
+        self.root = Some(node.0);
+
+        Ok(())
+    }
+
+    /// Returns the name of the given element.
+    #[must_use]
+    pub fn name(&self, node: XMLNodeId) -> &str {
+        &self.nodes[node.0].name
+    }
+
+    /// Returns the (key, value) attributes of the given element, values being escaped.
+    #[must_use]
+    pub fn attributes(&self, node: XMLNodeId) -> &[(XMLStr, XMLStr)] {
+        &self.nodes[node.0].attributes
+    }
+
+    /// Returns the text content of the given element, if any.
+    #[must_use]
+    pub fn text(&self, node: XMLNodeId) -> Option<&str> {
+        self.nodes[node.0].text.as_deref()
+    }
+
+    /// Returns the parent of the given element, if attached.
+    #[must_use]
+    pub fn parent(&self, node: XMLNodeId) -> Option<XMLNodeId> {
+        self.nodes[node.0].parent.map(XMLNodeId)
+    }
+
+    /// Returns the children of the given element, in order.
+    pub fn children(&self, node: XMLNodeId) -> impl Iterator<Item = XMLNodeId> + '_ {
+        std::iter::successors(self.nodes[node.0].first_child, |&child| {
+            self.nodes[child].next_sibling
+        })
+        .map(XMLNodeId)
+    }
+
+    /// Enables attributes sorting of the given element.
+    pub fn enable_attributes_sorting(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].sort_attributes = Some(true);
+    }
+
+    /// Disables attributes sorting of the given element.
+    pub fn disable_attributes_sorting(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].sort_attributes = Some(false);
+    }
+
+    /// Enables the indentation of the content of the given element and its descendants.
+    pub fn enable_indentation(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].indent = Some(true);
+    }
+
+    /// Disables the indentation of the content of the given element and its descendants.
+    pub fn disable_indentation(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].indent = Some(false);
+    }
+
+    /// Enables line breaks inside the given element and its descendants.
+    pub fn enable_line_breaks(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].break_lines = Some(true);
+    }
+
+    /// Disables line breaks inside the given element and its descendants.
+    pub fn disable_line_breaks(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].break_lines = Some(false);
+    }
+
+    /// Enables the expansion of the given element and its descendants when empty.
+    pub fn enable_empty_tags_expansion(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].expand_empty_tags = Some(true);
+    }
+
+    /// Disables the expansion of the given element and its descendants when empty.
+    pub fn disable_empty_tags_expansion(&mut self, node: XMLNodeId) {
+        self.nodes[node.0].expand_empty_tags = Some(false);
+    }
+
+    /// Adds the given name/value attribute to the given element.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the element.
+    /// * `name` - A string slice that holds the name of the attribute
+    /// * `value` - A string slice that holds the value of the attribute
+    pub fn add_attribute(&mut self, node: XMLNodeId, name: &str, value: &str) {
+        self.nodes[node.0]
+            .attributes
+            .push((name.to_owned().into(), escape_str(value).into()));
+    }
+
+    /// Adds the given name/value attribute to the given element, from `XMLStr` strings.
+    ///
+    /// Static and shared strings are not copied, unless the value needs to be escaped.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the element.
+    /// * `name` - The name of the attribute, as a static, shared or owned string.
+    /// * `value` - The value of the attribute, as a static, shared or owned string.
+    pub fn add_attribute_str<N: Into<XMLStr>, V: Into<XMLStr>>(
+        &mut self,
+        node: XMLNodeId,
+        name: N,
+        value: V,
+    ) {
+        self.nodes[node.0]
+            .attributes
+            .push((name.into(), escape_xml_str(value.into())));
+    }
+
+    /// Appends a detached element to the children of the given element.
+    ///
+    /// Raises `XMLError` if the parent holds text, or if the child is already attached,
+    /// is the root element or is an ancestor of the parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The identifier of the parent element.
+    /// * `child` - The identifier of the element to add as child.
+    pub fn add_child(&mut self, parent: XMLNodeId, child: XMLNodeId) -> Result<()> {
+        if self.nodes[parent.0].text.is_some() {
+            return Err(XMLError::InsertError(
+                "Cannot insert child inside an element with text".into(),
+            ));
+        }
+
+        let is_ancestor = std::iter::successors(Some(parent.0), |&node| self.nodes[node].parent)
+            .any(|node| node == child.0);
+        if is_ancestor || self.nodes[child.0].parent.is_some() || self.root == Some(child.0) {
+            return Err(XMLError::InsertError(
+                "Cannot insert an attached element as child".into(),
+            ));
+        }
+
+        self.link(parent, child);
+
+        Ok(())
+    }
+
+    /// Adds text content to the given element.
+    ///
+    /// Raises `XMLError` if trying to add text to a non-empty element.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the element.
+    /// * `text` - A string containing the text to add to the element
+    pub fn add_text(&mut self, node: XMLNodeId, text: String) -> Result<()> {
+        let node = &mut self.nodes[node.0];
+        if node.text.is_some() || node.first_child.is_some() {
+            return Err(XMLError::InsertError(
+                "Cannot insert text in a non-empty element".into(),
+            ));
+        }
+
+        node.text = Some(text);
+
+        Ok(())
+    }
+
+    /// Copies the given `XMLElement` tree into the `XMLArena`, returning the identifier
+    /// of its detached root.
+    ///
+    /// Lazy children and original formatting are not copied.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The `XMLElement` tree to copy.
+    pub fn add_element_tree(&mut self, element: &XMLElement) -> XMLNodeId {
+        let root = self.copy_element(element);
+
+        let mut stack = vec![(root, element.children().iter())];
+        while let Some((parent, children)) = stack.last_mut() {
+            let parent = *parent;
+            match children.next() {
+                Some(child) => {
+                    let node = self.copy_element(child);
+                    self.link(parent, node);
+                    stack.push((node, child.children().iter()));
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        root
+    }
+
+    /// Builds an `XMLElement` tree from the given element and its descendants.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the root of the tree to build.
+    #[must_use]
+    pub fn to_element(&self, node: XMLNodeId) -> XMLElement {
+        let mut stack = vec![(self.element(node.0), self.nodes[node.0].first_child)];
+        loop {
+            let Some((_, next)) = stack.last_mut() else {
+                unreachable!("the root element is popped last");
+            };
+
+            match *next {
+                Some(child) => {
+                    *next = self.nodes[child].next_sibling;
+                    stack.push((self.element(child), self.nodes[child].first_child));
+                }
+                None => {
+                    let Some((element, _)) = stack.pop() else {
+                        unreachable!("the stack is not empty");
+                    };
+                    match stack.last_mut() {
+                        Some((parent, _)) => push_child(parent, element),
+                        None => return element,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Renders the given element into the specified writer implementing Write trait.
+    ///
+    /// The output is identical to the one of the equivalent `XMLElement`.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the element to render.
+    /// * `writer` - An object to render the element to
+    /// * `options` - The `RenderOptions` to render the element with
+    pub fn render<W: Write>(
+        &self,
+        node: XMLNodeId,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<()> {
+        Renderer::arena(self, node.0, options.clone()).render(writer)
+    }
+
+    /// Internal method returning the node at the given index.
+    pub(crate) fn node(&self, index: usize) -> &Node {
+        &self.nodes[index]
+    }
+
+    /// Internal method adding a detached copy of the given element, without its children.
+    fn copy_element(&mut self, element: &XMLElement) -> XMLNodeId {
+        let node = self.new_element_from_name(element.name.clone());
+        let copy = &mut self.nodes[node.0];
+        copy.attributes.clone_from(&element.attributes);
+        copy.sort_attributes = element.sort_attributes;
+        copy.indent = element.indent;
+        copy.break_lines = element.break_lines;
+        copy.expand_empty_tags = element.expand_empty_tags;
+        copy.text = element.text().map(str::to_owned);
+
+        node
+    }
+
+    /// Internal method appending a detached element to the children of its parent.
+    fn link(&mut self, parent: XMLNodeId, child: XMLNodeId) {
+        match self.nodes[parent.0].last_child {
+            Some(last) => self.nodes[last].next_sibling = Some(child.0),
+            None => self.nodes[parent.0].first_child = Some(child.0),
+        }
+        self.nodes[parent.0].last_child = Some(child.0);
+        self.nodes[child.0].parent = Some(parent.0);
+    }
+
+    /// Internal method building an `XMLElement` from the given element, without its
+    /// children.
+    fn element(&self, index: usize) -> XMLElement {
+        let node = &self.nodes[index];
+        let mut element = XMLElement::from_name(node.name.clone());
+        element.attributes.clone_from(&node.attributes);
+        element.sort_attributes = node.sort_attributes;
+        element.indent = node.indent;
+        element.break_lines = node.break_lines;
+        element.expand_empty_tags = node.expand_empty_tags;
+        if let Some(text) = &node.text {
+            element.content = XMLElementContent::Text(text.clone());
+        }
+
+        element
+    }
+}
+
+impl From<&XMLElement> for XMLArena {
+    fn from(element: &XMLElement) -> Self {
+        let mut arena = Self::new();
+        let root = arena.add_element_tree(element);
+        arena.root = Some(root.0);
+
+        arena
+    }
+}
+
+/// Internal function appending a child to an `XMLElement` known not to hold text.
+fn push_child(parent: &mut XMLElement, child: XMLElement) {
+    match &mut parent.content {
+        XMLElementContent::Elements(elements) => Arc::make_mut(elements).push(child),
+        content => *content = XMLElementContent::Elements(Arc::new(vec![child])),
+    }
+}
@@ -0,0 +1,10 @@
+/// Enum representing the layouts of the attributes of start tags exceeding the max line width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum XMLAttributeWrapping {
+    /// Attributes are aligned under the first one, kept next to the element name. The
+    /// default one.
+    Aligned,
+
+    /// Attributes following the first one are indented one level deeper than the element.
+    Indented,
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::XMLElement;
 
 /// An enum value representing the types of XML contents
@@ -6,8 +8,8 @@ pub enum XMLElementContent {
     /// No XML content.
     Empty,
 
-    /// The content is a list of XML elements.
-    Elements(Vec<XMLElement>),
+    /// The content is a list of XML elements, shared between clones until mutated.
+    Elements(Arc<Vec<XMLElement>>),
 
     /// The content is a textual string.
     Text(String),
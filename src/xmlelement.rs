@@ -1,23 +1,61 @@
 use std::io::Write;
+use std::sync::{Arc, Mutex, PoisonError};
 
-use crate::{Result, XMLElementContent, XMLError, escape_str};
+use crate::renderer::{Elements, LengthWriter, RenderCache, Renderer};
+use crate::utils::escape_xml_str;
+use crate::xmltrivia::XMLTrivia;
+use crate::{RenderOptions, Result, XMLElementContent, XMLError, XMLStr, escape_str};
 
 /// Structure representing an XML element field.
+///
+/// Cloning an `XMLElement` does not copy its children, which are shared with the clone
+/// and only copied once either of them is mutated. A large fragment can thus be added
+/// in many places of a document for the cost of a single copy.
 #[derive(Clone)]
 pub struct XMLElement {
     /// The name of the XML element.
-    name: String,
+    pub(crate) name: XMLStr,
 
     /// A list of tuple representing (key, value) attributes.
-    attributes: Vec<(String, String)>,
+    pub(crate) attributes: Vec<(XMLStr, XMLStr)>,
 
     /// A boolean representing whether we want attributes to be sorted.
     ///
     /// If not set, defaults to the root's `XMLELement`.
-    sort_attributes: Option<bool>,
+    pub(crate) sort_attributes: Option<bool>,
+
+    /// Whether this element and its descendants are indented.
+    ///
+    /// If not set, defaults to the parent's one.
+    pub(crate) indent: Option<bool>,
+
+    /// Whether lines are broken after the tags of this element and its descendants.
+    ///
+    /// If not set, defaults to the parent's one.
+    pub(crate) break_lines: Option<bool>,
+
+    /// Whether this element and its empty descendants are written with an end tag.
+    ///
+    /// If not set, defaults to the parent's one.
+    pub(crate) expand_empty_tags: Option<bool>,
 
     /// The content of this XML element.
-    content: XMLElementContent,
+    pub(crate) content: XMLElementContent,
+
+    /// The original formatting of this XML element, only set by lossless parsing.
+    ///
+    /// When set, it is honored instead of the indentation and line breaks settings.
+    pub(crate) trivia: Option<Box<XMLTrivia>>,
+
+    /// A source of children only pulled while rendering, after the other children.
+    ///
+    /// It is shared between clones, the first rendering consuming it.
+    lazy_children: Option<Arc<Mutex<Option<Elements<'static>>>>>,
+
+    /// The renderings of this XML element, if caching is enabled.
+    ///
+    /// It is shared between clones until either is mutated.
+    cache: Option<Arc<RenderCache>>,
 }
 
 impl XMLElement {
@@ -28,11 +66,69 @@ impl XMLElement {
     /// * `name` - A string slice that holds the name of the XML element.
     #[must_use]
     pub fn new(name: &str) -> Self {
+        Self::from_name(name.to_owned())
+    }
+
+    /// Instantiates a new `XMLElement` object from a `XMLStr` name.
+    ///
+    /// Static and shared names are not copied, which avoids allocating the same name
+    /// for each of many elements.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the XML element, as a static, shared or owned string.
+    #[must_use]
+    pub fn from_name<N: Into<XMLStr>>(name: N) -> Self {
         Self {
             name: name.into(),
             attributes: Vec::new(),
             sort_attributes: None,
+            indent: None,
+            break_lines: None,
+            expand_empty_tags: None,
             content: XMLElementContent::Empty,
+            trivia: None,
+            lazy_children: None,
+            cache: None,
+        }
+    }
+
+    /// Returns the name of the `XMLElement`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the (key, value) attributes of the `XMLElement`, values being escaped.
+    #[must_use]
+    pub fn attributes(&self) -> &[(XMLStr, XMLStr)] {
+        &self.attributes
+    }
+
+    /// Returns the mutable `XMLElement` children, which is empty for text elements.
+    pub fn children_mut(&mut self) -> &mut [Self] {
+        self.invalidate();
+        match &mut self.content {
+            XMLElementContent::Elements(elements) => Arc::make_mut(elements).as_mut_slice(),
+            _ => &mut [],
+        }
+    }
+
+    /// Returns the `XMLElement` children, which is empty for text elements.
+    #[must_use]
+    pub fn children(&self) -> &[Self] {
+        match &self.content {
+            XMLElementContent::Elements(elements) => elements,
+            _ => &[],
+        }
+    }
+
+    /// Returns the text content of the `XMLElement`, if any.
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        match &self.content {
+            XMLElementContent::Text(text) => Some(text),
+            _ => None,
         }
     }
 
@@ -46,6 +142,48 @@ impl XMLElement {
         self.sort_attributes = Some(false);
     }
 
+    /// Enables the indentation of the content of this element and its descendants,
+    /// whatever the rendering options, unless a descendant disables it.
+    pub const fn enable_indentation(&mut self) {
+        self.indent = Some(true);
+    }
+
+    /// Disables the indentation of the content of this element and its descendants,
+    /// unless a descendant enables it.
+    ///
+    /// The element itself is indented as its siblings are.
+    pub const fn disable_indentation(&mut self) {
+        self.indent = Some(false);
+    }
+
+    /// Enables line breaks inside this element and its descendants, whatever the rendering
+    /// options, unless a descendant disables them.
+    pub const fn enable_line_breaks(&mut self) {
+        self.break_lines = Some(true);
+    }
+
+    /// Disables line breaks inside this element and its descendants, unless a descendant
+    /// enables them.
+    ///
+    /// The element itself is followed by a line break as its siblings are, so that it can be
+    /// kept on a single line inside an indented document. Its content is not indented
+    /// either, unless indentation is enabled on it or on a descendant.
+    pub const fn disable_line_breaks(&mut self) {
+        self.break_lines = Some(false);
+    }
+
+    /// Enables the expansion of this element and its descendants when empty, whatever the
+    /// rendering options, unless a descendant disables it.
+    pub const fn enable_empty_tags_expansion(&mut self) {
+        self.expand_empty_tags = Some(true);
+    }
+
+    /// Disables the expansion of this element and its descendants when empty, unless a
+    /// descendant enables it.
+    pub const fn disable_empty_tags_expansion(&mut self) {
+        self.expand_empty_tags = Some(false);
+    }
+
     /// Adds the given name/value attribute to the `XMLElement`.
     ///
     /// # Arguments
@@ -53,7 +191,51 @@ impl XMLElement {
     /// * `name` - A string slice that holds the name of the attribute
     /// * `value` - A string slice that holds the value of the attribute
     pub fn add_attribute(&mut self, name: &str, value: &str) {
-        self.attributes.push((name.into(), escape_str(value)));
+        self.invalidate();
+        self.attributes
+            .push((name.to_owned().into(), escape_str(value).into()));
+    }
+
+    /// Adds the given name/value attribute to the `XMLElement`, from `XMLStr` strings.
+    ///
+    /// Static and shared strings are not copied, unless the value needs to be escaped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the attribute, as a static, shared or owned string.
+    /// * `value` - The value of the attribute, as a static, shared or owned string.
+    pub fn add_attribute_str<N: Into<XMLStr>, V: Into<XMLStr>>(&mut self, name: N, value: V) {
+        self.invalidate();
+        self.attributes
+            .push((name.into(), escape_xml_str(value.into())));
+    }
+
+    /// Sets the value of the given attribute, adding it if it does not exist yet.
+    ///
+    /// The attribute keeps its position, and its original formatting if parsed losslessly.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the attribute
+    /// * `value` - A string slice that holds the new value of the attribute
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        self.invalidate();
+        match self.attributes.iter_mut().find(|(key, _)| key == name) {
+            Some((_, current)) => *current = escape_str(value).into(),
+            None => self.add_attribute(name, value),
+        }
+    }
+
+    /// Internal method adding an attribute whose value is already escaped.
+    pub(crate) fn add_escaped_attribute(&mut self, name: String, value: String) {
+        self.invalidate();
+        self.attributes.push((name.into(), value.into()));
+    }
+
+    /// Internal method attaching the original formatting of a losslessly parsed element.
+    pub(crate) fn set_trivia(&mut self, trivia: XMLTrivia) {
+        self.invalidate();
+        self.trivia = Some(Box::new(trivia));
     }
 
     /// Adds a new `XMLElement` child object to the references `XMLElement`.
@@ -64,12 +246,13 @@ impl XMLElement {
     ///
     /// * `element` - A `XMLElement` object to add as child
     pub fn add_child(&mut self, element: Self) -> Result<()> {
+        self.invalidate();
         match self.content {
             XMLElementContent::Empty => {
-                self.content = XMLElementContent::Elements(vec![element]);
+                self.content = XMLElementContent::Elements(Arc::new(vec![element]));
             }
             XMLElementContent::Elements(ref mut e) => {
-                e.push(element);
+                Arc::make_mut(e).push(element);
             }
             XMLElementContent::Text(_) => {
                 return Err(XMLError::InsertError(
@@ -78,9 +261,100 @@ impl XMLElement {
             }
         }
 
+        // New children are laid out like their previous sibling, or like the end tag
+        if let Some(trivia) = &mut self.trivia {
+            trivia.self_closing = false;
+            let last = trivia.gaps.len().saturating_sub(1);
+            let previous = last.checked_sub(1).unwrap_or(last);
+            let gap = trivia.gaps.get(previous).cloned().unwrap_or_default();
+            trivia.gaps.insert(last, gap);
+        }
+
         Ok(())
     }
 
+    /// Sets a source of children only pulled while rendering, after the other children.
+    ///
+    /// It allows describing a large element, such as rows pulled from a database cursor,
+    /// without materializing all of its children. Closures can be used as source through
+    /// `std::iter::from_fn`. The source is consumed by the first rendering of the
+    /// element, or of any of its clones.
+    ///
+    /// Raises `XMLError` if trying to add children to a text `XMLElement`.
+    ///
+    /// # Arguments
+    ///
+    /// * `children` - An iterator producing the `XMLElement` children.
+    pub fn set_lazy_children<I>(&mut self, children: I) -> Result<()>
+    where
+        I: IntoIterator<Item = Self>,
+        I::IntoIter: Send + 'static,
+    {
+        self.invalidate();
+        if let XMLElementContent::Text(_) = self.content {
+            return Err(XMLError::InsertError(
+                "Cannot insert child inside an element with text".into(),
+            ));
+        }
+
+        self.lazy_children = Some(Arc::new(Mutex::new(Some(Box::new(children.into_iter())))));
+
+        if let Some(trivia) = &mut self.trivia {
+            trivia.self_closing = false;
+        }
+
+        Ok(())
+    }
+
+    /// Enables the caching of the rendering of this `XMLElement`, children included.
+    ///
+    /// Renderings are kept per formatting options and indentation level, and reused as
+    /// long as the element is not mutated. Mutating a descendant requires mutating this
+    /// element first, which discards its renderings. Clones share the renderings until
+    /// either is mutated, so that a document can be regenerated from a clone of its root,
+    /// only re-rendering its mutated parts.
+    ///
+    /// Elements pulling lazy children are not cached, their source being consumed.
+    pub fn enable_render_cache(&mut self) {
+        self.cache = Some(Arc::default());
+    }
+
+    /// Disables the caching of the rendering of this `XMLElement`, discarding its renderings.
+    pub fn disable_render_cache(&mut self) {
+        self.cache = None;
+    }
+
+    /// Internal method returning the renderings of this element, if caching is enabled.
+    pub(crate) const fn render_cache(&self) -> Option<&Arc<RenderCache>> {
+        self.cache.as_ref()
+    }
+
+    /// Internal method discarding the renderings of this element before it is mutated.
+    ///
+    /// Renderings shared with clones are left to them.
+    fn invalidate(&mut self) {
+        if self.cache.is_some() {
+            self.cache = Some(Arc::default());
+        }
+    }
+
+    /// Internal method returning whether the source of lazy children is not consumed yet.
+    pub(crate) fn has_lazy_children(&self) -> bool {
+        self.lazy_children.as_ref().is_some_and(|source| {
+            source
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .is_some()
+        })
+    }
+
+    /// Internal method taking the source of lazy children, if not consumed yet.
+    pub(crate) fn take_lazy_children(&self) -> Option<Elements<'static>> {
+        self.lazy_children
+            .as_ref()
+            .and_then(|source| source.lock().unwrap_or_else(PoisonError::into_inner).take())
+    }
+
     /// Adds text content to a `XMLElement` object.
     ///
     /// Raises `XMLError` if trying to add text to a non-empty object.
@@ -89,8 +363,9 @@ impl XMLElement {
     ///
     /// * `text` - A string containing the text to add to the object
     pub fn add_text(&mut self, text: String) -> Result<()> {
+        self.invalidate();
         match self.content {
-            XMLElementContent::Empty => {
+            XMLElementContent::Empty if self.lazy_children.is_none() => {
                 self.content = XMLElementContent::Text(text);
             }
             _ => {
@@ -100,33 +375,28 @@ impl XMLElement {
             }
         }
 
+        if let Some(trivia) = &mut self.trivia {
+            trivia.self_closing = false;
+        }
+
         Ok(())
     }
 
-    /// Internal method rendering attribute list to a String.
+    /// Replaces the content of a `XMLElement` object with the given text.
     ///
-    /// # Arguments
+    /// Lazy children are dropped as well.
     ///
-    /// * `should_sort` - A boolean indicating whether we should sort these atttibutes.
-    fn attributes_as_string(&self, should_sort: bool) -> String {
-        if self.attributes.is_empty() {
-            String::default()
-        } else {
-            let mut attributes = self.attributes.clone();
-
-            // Giving priority to the element boolean, and taking the global xml if not set
-            let should_sort_attributes = self.sort_attributes.unwrap_or(should_sort);
-
-            if should_sort_attributes {
-                attributes.sort();
-            }
-
-            let mut result = String::new();
+    /// # Arguments
+    ///
+    /// * `text` - A string containing the new text of the object
+    pub fn set_text(&mut self, text: String) {
+        self.invalidate();
+        self.content = XMLElementContent::Text(text);
+        self.lazy_children = None;
 
-            for (k, v) in &attributes {
-                result = format!("{result} {k}=\"{v}\"");
-            }
-            result
+        if let Some(trivia) = &mut self.trivia {
+            trivia.self_closing = false;
+            trivia.gaps = vec![String::new()];
         }
     }
 
@@ -137,87 +407,66 @@ impl XMLElement {
     /// # Arguments
     ///
     /// * `writer` - An object to render the referenced `XMLElement` to
-    pub fn render<W: Write>(
-        &self,
-        writer: &mut W,
-        should_sort: bool,
-        should_indent: bool,
-        should_break_lines: bool,
-        should_expand_empty_tags: bool,
-    ) -> Result<()> {
-        self.render_level(
-            writer,
-            0,
-            should_sort,
-            should_indent,
-            should_break_lines,
-            should_expand_empty_tags,
-        )
+    /// * `options` - The `RenderOptions` to render the element with
+    pub fn render<W: Write>(&self, writer: &mut W, options: &RenderOptions) -> Result<()> {
+        self.render_level(writer, 0, options)
     }
 
-    /// Internal method rendering and indenting a `XMLElement` object
+    /// Computes the exact number of bytes `render` would write with the same options,
+    /// without writing anything.
+    ///
+    /// Raises `XMLError` if the element or one of its descendants has lazy children, which
+    /// cannot be measured without being consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `RenderOptions` the element would be rendered with
+    pub fn rendered_len(&self, options: &RenderOptions) -> Result<usize> {
+        let mut writer = LengthWriter::default();
+        Renderer::new(self, 0, options.clone())
+            .measuring()
+            .render(&mut writer)?;
+
+        Ok(writer.0)
+    }
+
+    /// Internal method rendering and indenting a `XMLElement` object, without recursion
     ///
     /// # Arguments
     ///
     /// * `writer` - An object to render the referenced `XMLElement` to
     /// * `level` - An usize representing the depth of the XML tree. Used to indent the object.
-    fn render_level<W: Write>(
+    /// * `options` - The `RenderOptions` to apply.
+    pub(crate) fn render_level<W: Write>(
         &self,
         writer: &mut W,
         level: usize,
-        should_sort: bool,
-        should_indent: bool,
-        should_break_lines: bool,
-        should_expand_empty_tags: bool,
+        options: &RenderOptions,
     ) -> Result<()> {
-        let indent = if should_indent {
-            "\t".repeat(level)
-        } else {
-            String::new()
-        };
-        let suffix = if should_break_lines { "\n" } else { "" };
+        Renderer::new(self, level, options.clone()).render(writer)
+    }
+}
 
-        let attributes = self.attributes_as_string(should_sort);
+impl Drop for XMLElement {
+    fn drop(&mut self) {
+        // Descendants are moved into a flat list, so that deep trees do not overflow the stack.
+        // Children still shared with a clone are left to it.
+        let XMLElementContent::Elements(elements) =
+            std::mem::replace(&mut self.content, XMLElementContent::Empty)
+        else {
+            return;
+        };
+        let Some(mut elements) = Arc::into_inner(elements) else {
+            return;
+        };
 
-        match &self.content {
-            XMLElementContent::Empty => {
-                if should_expand_empty_tags {
-                    write!(
-                        writer,
-                        "{}<{}{}></{}>{}",
-                        indent, self.name, attributes, self.name, suffix
-                    )?;
-                } else {
-                    write!(
-                        writer,
-                        "{}<{}{} />{}",
-                        indent, self.name, attributes, suffix
-                    )?;
-                }
-            }
-            XMLElementContent::Elements(elements) => {
-                write!(writer, "{}<{}{}>{}", indent, self.name, attributes, suffix)?;
-                for elem in elements {
-                    elem.render_level(
-                        writer,
-                        level + 1,
-                        should_sort,
-                        should_indent,
-                        should_break_lines,
-                        should_expand_empty_tags,
-                    )?;
-                }
-                write!(writer, "{}</{}>{}", indent, self.name, suffix)?;
-            }
-            XMLElementContent::Text(text) => {
-                write!(
-                    writer,
-                    "{}<{}{}>{}</{}>{}",
-                    indent, self.name, attributes, text, self.name, suffix
-                )?;
+        while let Some(mut element) = elements.pop() {
+            if let XMLElementContent::Elements(children) =
+                std::mem::replace(&mut element.content, XMLElementContent::Empty)
+                && let Some(children) = Arc::into_inner(children)
+            {
+                elements.extend(children);
             }
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,92 @@
+/// Unicode code points of the Windows-1252 bytes from `0x80` to `0x9F`.
+///
+/// Undefined bytes are mapped to the C1 control character of the same value.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Enum representing all currently supported document encodings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XMLEncoding {
+    /// UTF-8, the default XML encoding.
+    UTF8,
+
+    /// UTF-16, little-endian.
+    UTF16LE,
+
+    /// UTF-16, big-endian.
+    UTF16BE,
+
+    /// US-ASCII, the 7-bit subset of UTF-8.
+    ASCII,
+
+    /// ISO-8859-1, also known as Latin-1.
+    ISO8859_1,
+
+    /// Windows-1252, the Western European Windows code page.
+    Windows1252,
+}
+
+impl XMLEncoding {
+    /// Returns the encoding matching the given label, as found in an XML declaration.
+    ///
+    /// Labels are case-insensitive. `UTF-16` is resolved as `UTF16LE`, the actual byte
+    /// order of a document being given by its byte order mark.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - A string slice that holds the label of the encoding.
+    #[must_use]
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Self::UTF8),
+            "us-ascii" | "ascii" => Some(Self::ASCII),
+            "utf-16" | "utf-16le" => Some(Self::UTF16LE),
+            "utf-16be" => Some(Self::UTF16BE),
+            "iso-8859-1" | "iso_8859-1" | "latin1" | "l1" => Some(Self::ISO8859_1),
+            "windows-1252" | "cp1252" => Some(Self::Windows1252),
+            _ => None,
+        }
+    }
+
+    /// Internal method decoding a byte of a single-byte encoding.
+    ///
+    /// Bytes above `0x7F` of US-ASCII documents are leniently decoded as ISO-8859-1.
+    pub(crate) fn decode_byte(self, byte: u8) -> char {
+        match (self, byte) {
+            (Self::Windows1252, 0x80..=0x9F) => WINDOWS_1252_HIGH[usize::from(byte - 0x80)],
+            _ => char::from(byte),
+        }
+    }
+
+    /// Internal method encoding a character into a single-byte encoding.
+    ///
+    /// Returns `None` if the character cannot be represented.
+    pub(crate) fn encode_byte(self, c: char) -> Option<u8> {
+        match self {
+            Self::Windows1252 => match WINDOWS_1252_HIGH.iter().position(|&h| h == c) {
+                Some(index) => u8::try_from(index + 0x80).ok(),
+                None => u8::try_from(u32::from(c))
+                    .ok()
+                    .filter(|byte| !(0x80..=0x9F).contains(byte)),
+            },
+            Self::ASCII => u8::try_from(u32::from(c)).ok().filter(u8::is_ascii),
+            _ => u8::try_from(u32::from(c)).ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for XMLEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UTF8 => write!(f, "UTF-8"),
+            Self::UTF16LE | Self::UTF16BE => write!(f, "UTF-16"),
+            Self::ASCII => write!(f, "US-ASCII"),
+            Self::ISO8859_1 => write!(f, "ISO-8859-1"),
+            Self::Windows1252 => write!(f, "windows-1252"),
+        }
+    }
+}
@@ -10,6 +10,27 @@ pub enum XMLError {
     InsertError(String),
     /// Thrown when the given `Writer` cannot be written to.
     IOError(String),
+    /// Thrown when the given document is not well-formed XML.
+    ParseError(String),
+    /// Thrown when the document references an external entity, which are never resolved.
+    ExternalEntity(String),
+    /// Thrown when entity references are nested deeper than the given limit.
+    EntityDepthExceeded(usize),
+    /// Thrown when entity expansions produce more bytes than the given limit.
+    EntityExpansionExceeded(usize),
+    /// Thrown when elements are nested deeper than the given limit.
+    DepthLimitExceeded(usize),
+    /// Thrown when an element has more attributes than the given limit.
+    TooManyAttributes(usize),
+    /// Thrown when a name is longer than the given limit.
+    NameTooLong(usize),
+    /// Thrown when a textual content is longer than the given limit.
+    TextTooLong(usize),
+    /// Thrown when the calls made to a `XMLWriter` would not produce a well-formed document.
+    WriterError(String),
+    /// Thrown when the rendered length of a document cannot be computed without consuming
+    /// the lazy children of an element.
+    MeasureError(String),
 }
 
 impl From<std::io::Error> for XMLError {
@@ -23,6 +44,20 @@ impl Debug for XMLError {
         match self {
             Self::InsertError(e) => write!(f, "Error encountered during insertion: {e}"),
             Self::IOError(e) => write!(f, "Error encountered during write: {e}"),
+            Self::ParseError(e) => write!(f, "Error encountered during parsing: {e}"),
+            Self::ExternalEntity(e) => write!(f, "External entity `{e}` cannot be resolved"),
+            Self::EntityDepthExceeded(l) => {
+                write!(f, "Entity references are nested deeper than {l} levels")
+            }
+            Self::EntityExpansionExceeded(l) => {
+                write!(f, "Entity expansions produce more than {l} bytes")
+            }
+            Self::DepthLimitExceeded(l) => write!(f, "Elements are nested deeper than {l} levels"),
+            Self::TooManyAttributes(l) => write!(f, "Element has more than {l} attributes"),
+            Self::NameTooLong(l) => write!(f, "Name is longer than {l} bytes"),
+            Self::TextTooLong(l) => write!(f, "Textual content is longer than {l} bytes"),
+            Self::WriterError(e) => write!(f, "Error encountered during streaming write: {e}"),
+            Self::MeasureError(e) => write!(f, "Error encountered during measurement: {e}"),
         }
     }
 }
@@ -0,0 +1,51 @@
+/// An enum value representing the events yielded by an `XMLReader`.
+///
+/// Textual values (text, CDATA and attribute values) are given unescaped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XMLEvent {
+    /// The `<?xml ... ?>` declaration of the document.
+    Declaration {
+        /// The declared XML version.
+        version: String,
+        /// The declared encoding, if any.
+        encoding: Option<String>,
+        /// The declared standalone status, if any.
+        standalone: Option<bool>,
+    },
+
+    /// The start of an element.
+    ///
+    /// Self-closing tags yield a `StartElement` immediately followed by an `EndElement`.
+    StartElement {
+        /// The name of the element.
+        name: String,
+        /// A list of tuple representing (key, value) attributes.
+        attributes: Vec<(String, String)>,
+    },
+
+    /// The end of an element.
+    EndElement {
+        /// The name of the element.
+        name: String,
+    },
+
+    /// Textual content, including whitespaces between elements.
+    Text(String),
+
+    /// The content of a `<![CDATA[ ... ]]>` section.
+    CData(String),
+
+    /// The content of a `<!-- ... -->` comment.
+    Comment(String),
+
+    /// A `<?target data?>` processing instruction.
+    ProcessingInstruction {
+        /// The target of the processing instruction.
+        target: String,
+        /// The data following the target, if any.
+        data: Option<String>,
+    },
+
+    /// The raw content of the `<!DOCTYPE ... >` declaration.
+    DocumentType(String),
+}
@@ -0,0 +1,133 @@
+/// Limits enforced while reading a document, protecting against malicious inputs.
+///
+/// The default limits are suited for untrusted documents, while `XMLLimits::trusted()`,
+/// used when no limits are given, should only be used for trusted ones. External entities
+/// are never resolved, whatever the limits are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XMLLimits {
+    /// Maximum nesting depth of elements.
+    ///
+    /// Defaults to `256`.
+    pub(crate) max_depth: usize,
+
+    /// Maximum number of attributes of an element.
+    ///
+    /// Defaults to `256`.
+    pub(crate) max_attributes: usize,
+
+    /// Maximum length in bytes of element, attribute, entity and processing instruction names.
+    ///
+    /// Defaults to `1024`.
+    pub(crate) max_name_length: usize,
+
+    /// Maximum length in bytes of a textual content, which includes text, CDATA sections,
+    /// comments, attribute values and the document type declaration.
+    ///
+    /// Defaults to `16 MiB`.
+    pub(crate) max_text_length: usize,
+
+    /// Maximum nesting depth of entity references inside entity values.
+    ///
+    /// Defaults to `4`.
+    pub(crate) max_entity_depth: usize,
+
+    /// Maximum total length in bytes produced by entity expansions in the whole document.
+    ///
+    /// Defaults to `1 MiB`.
+    pub(crate) max_entity_expansion: usize,
+}
+
+impl Default for XMLLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 256,
+            max_attributes: 256,
+            max_name_length: 1024,
+            max_text_length: 16 * 1024 * 1024,
+            max_entity_depth: 4,
+            max_entity_expansion: 1024 * 1024,
+        }
+    }
+}
+
+impl XMLLimits {
+    /// Builds new `XMLLimits` suited for untrusted documents.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds new `XMLLimits` enforcing nothing, which must only be used for trusted documents.
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            max_attributes: usize::MAX,
+            max_name_length: usize::MAX,
+            max_text_length: usize::MAX,
+            max_entity_depth: usize::MAX,
+            max_entity_expansion: usize::MAX,
+        }
+    }
+
+    /// Builds new `XMLLimits` suited for trusted documents, only bounding entity expansion.
+    ///
+    /// Entity references may be nested up to `64` levels, producing up to `64 MiB`, so
+    /// that a document declaring exponentially growing entities cannot exhaust memory.
+    #[must_use]
+    pub const fn trusted() -> Self {
+        Self {
+            max_entity_depth: 64,
+            max_entity_expansion: 64 * 1024 * 1024,
+            ..Self::unlimited()
+        }
+    }
+
+    /// Sets the maximum nesting depth of elements.
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+
+        self
+    }
+
+    /// Sets the maximum number of attributes of an element.
+    #[must_use]
+    pub const fn max_attributes(mut self, max_attributes: usize) -> Self {
+        self.max_attributes = max_attributes;
+
+        self
+    }
+
+    /// Sets the maximum length in bytes of names.
+    #[must_use]
+    pub const fn max_name_length(mut self, max_name_length: usize) -> Self {
+        self.max_name_length = max_name_length;
+
+        self
+    }
+
+    /// Sets the maximum length in bytes of textual contents.
+    #[must_use]
+    pub const fn max_text_length(mut self, max_text_length: usize) -> Self {
+        self.max_text_length = max_text_length;
+
+        self
+    }
+
+    /// Sets the maximum nesting depth of entity references.
+    #[must_use]
+    pub const fn max_entity_depth(mut self, max_entity_depth: usize) -> Self {
+        self.max_entity_depth = max_entity_depth;
+
+        self
+    }
+
+    /// Sets the maximum total length in bytes produced by entity expansions.
+    #[must_use]
+    pub const fn max_entity_expansion(mut self, max_entity_expansion: usize) -> Self {
+        self.max_entity_expansion = max_entity_expansion;
+
+        self
+    }
+}
@@ -0,0 +1,24 @@
+/// Enum representing the line endings written between rendered elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum XMLLineEnding {
+    /// A line feed, as used by Unix systems. The default one.
+    LF,
+
+    /// A carriage return followed by a line feed, as used by Windows systems.
+    CRLF,
+
+    /// A carriage return, as used by classic Mac OS systems.
+    CR,
+}
+
+impl XMLLineEnding {
+    /// Returns the characters of the line ending.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::LF => "\n",
+            Self::CRLF => "\r\n",
+            Self::CR => "\r",
+        }
+    }
+}
@@ -0,0 +1,905 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::io::BufRead;
+
+use crate::transcoding::DecodingReader;
+use crate::utils::{is_valid_name, is_whitespace, is_whitespace_char, predefined_entity};
+use crate::{Result, XMLEncoding, XMLError, XMLEvent, XMLLimits};
+
+/// The UTF-8 byte order mark, skipped if found at the very start of the document.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// An entity declared in the document type declaration.
+enum Entity {
+    /// An internal entity, holding its replacement text.
+    Internal(String),
+
+    /// An external entity, which is never resolved.
+    External,
+}
+
+/// The markup being read inside the internal subset of a document type declaration.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SubsetMarkup {
+    /// Between markup, where quotes are not meaningful.
+    None,
+
+    /// A markup declaration, such as `<!ENTITY>`.
+    Declaration,
+
+    /// A comment, skipped up to its end.
+    Comment,
+
+    /// A processing instruction, skipped up to its end.
+    Instruction,
+}
+
+/// Limits enforced on each part of a start or end tag while it is being read, so that an
+/// oversized name, attribute value or whitespace run is rejected before being buffered whole.
+struct TagLimiter {
+    /// The limits enforced while reading the document.
+    limits: XMLLimits,
+
+    /// Number of attribute values found so far.
+    attributes: usize,
+
+    /// Length in bytes of the current name, attribute value or whitespace run.
+    length: usize,
+
+    /// Whether the last byte read outside of quotes was a whitespace.
+    whitespace: bool,
+}
+
+impl TagLimiter {
+    /// Instantiates a new `TagLimiter` enforcing the given limits.
+    const fn new(limits: XMLLimits) -> Self {
+        Self {
+            limits,
+            attributes: 0,
+            length: 0,
+            whitespace: false,
+        }
+    }
+
+    /// Accounts for the given byte of the tag, raising an error as soon as a limit is exceeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `quote` - The quote of the attribute value the byte is read in, if any.
+    /// * `byte` - The byte being read.
+    const fn check(&mut self, quote: Option<u8>, byte: u8) -> Result<()> {
+        match quote {
+            // Closing quote
+            Some(q) if q == byte => self.length = 0,
+            Some(_) => {
+                self.length += 1;
+                if self.length > self.limits.max_text_length {
+                    return Err(XMLError::TextTooLong(self.limits.max_text_length));
+                }
+            }
+            None => match byte {
+                b'"' | b'\'' => {
+                    self.attributes += 1;
+                    if self.attributes > self.limits.max_attributes {
+                        return Err(XMLError::TooManyAttributes(self.limits.max_attributes));
+                    }
+                    self.length = 0;
+                }
+                b'=' | b'/' => self.length = 0,
+                _ => {
+                    let whitespace = byte.is_ascii_whitespace();
+                    if whitespace != self.whitespace {
+                        self.whitespace = whitespace;
+                        self.length = 0;
+                    }
+                    self.length += 1;
+
+                    if whitespace && self.length > self.limits.max_text_length {
+                        return Err(XMLError::TextTooLong(self.limits.max_text_length));
+                    }
+                    if !whitespace && self.length > self.limits.max_name_length {
+                        return Err(XMLError::NameTooLong(self.limits.max_name_length));
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Incremental pull reader yielding `XMLEvent`s from any `BufRead` source.
+///
+/// Only the markup or text currently being read is held in memory, allowing
+/// documents far bigger than the available memory to be inspected without
+/// materializing an `XMLElement` tree.
+///
+/// The document encoding is detected from its byte order mark or its XML declaration,
+/// and transparently decoded: all the yielded events are UTF-8.
+pub struct XMLReader<R: BufRead> {
+    /// The source the document is read from, decoded into UTF-8.
+    reader: DecodingReader<R>,
+
+    /// Buffer holding the raw bytes of the current token, reused across events.
+    buffer: Vec<u8>,
+
+    /// Names of the currently opened elements, from the root to the deepest one.
+    open_elements: Vec<String>,
+
+    /// Name of the self-closing element whose `EndElement` event is still to be yielded.
+    pending_end: Option<String>,
+
+    /// Whether anything has been read yet, the XML declaration being only allowed first.
+    started: bool,
+
+    /// Whether a `<!DOCTYPE>` declaration has already been read.
+    doctype_seen: bool,
+
+    /// Whether the root element has already been opened.
+    root_seen: bool,
+
+    /// Whether the reader reached the end of the document or failed.
+    done: bool,
+
+    /// Number of bytes consumed so far, used to locate errors.
+    offset: usize,
+
+    /// The limits enforced while reading the document.
+    limits: XMLLimits,
+
+    /// General entities declared in the document type declaration.
+    entities: HashMap<String, Entity>,
+
+    /// Names of the entities currently being expanded, used to detect recursion.
+    expanding: HashSet<String>,
+
+    /// Total number of bytes produced by entity expansions so far.
+    expanded: usize,
+
+    /// Raw bytes consumed while reading the last event, only recorded for lossless parsing.
+    raw: Option<Vec<u8>>,
+}
+
+impl<R: BufRead> XMLReader<R> {
+    /// Instantiates a new `XMLReader` reading from the given source.
+    ///
+    /// Only entity expansion is limited, by `XMLLimits::trusted()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - An object implementing `BufRead` to read the document from.
+    pub fn new(reader: R) -> Self {
+        Self::with_limits(reader, XMLLimits::trusted())
+    }
+
+    /// Instantiates a new `XMLReader` enforcing the given limits, suited for untrusted documents.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - An object implementing `BufRead` to read the document from.
+    /// * `limits` - The `XMLLimits` to enforce while reading.
+    pub fn with_limits(reader: R, limits: XMLLimits) -> Self {
+        Self {
+            reader: DecodingReader::new(reader),
+            buffer: Vec::new(),
+            open_elements: Vec::new(),
+            pending_end: None,
+            started: false,
+            doctype_seen: false,
+            root_seen: false,
+            done: false,
+            offset: 0,
+            limits,
+            entities: HashMap::new(),
+            expanding: HashSet::new(),
+            expanded: 0,
+            raw: None,
+        }
+    }
+
+    /// Returns the number of currently opened elements.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.open_elements.len()
+    }
+
+    /// Internal method returning the limits enforced while reading the document.
+    pub(crate) const fn limits(&self) -> &XMLLimits {
+        &self.limits
+    }
+
+    /// Internal method enabling the recording of the raw bytes consumed for each event.
+    pub(crate) fn record_raw(&mut self) {
+        self.raw = Some(Vec::new());
+    }
+
+    /// Internal method returning the raw source of the last event, including the
+    /// whitespaces skipped outside of the root element before it.
+    ///
+    /// After the end of the document, returns the trailing whitespaces.
+    pub(crate) fn raw(&self) -> &str {
+        self.raw
+            .as_deref()
+            .and_then(|raw| std::str::from_utf8(raw).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns the detected encoding of the document, once its first event has been read.
+    #[must_use]
+    pub const fn encoding(&self) -> Option<XMLEncoding> {
+        self.reader.encoding()
+    }
+
+    /// Consumes the reader, returning the underlying source.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Reads the next event from the document.
+    ///
+    /// Returns `None` once the whole document has been read.
+    ///
+    /// Raises `XMLError` if the document is not well-formed or cannot be read.
+    pub fn next_event(&mut self) -> Result<Option<XMLEvent>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let event = self.read_event();
+        if !matches!(event, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        event
+    }
+
+    /// Internal method reading the next event, regardless of the reader state.
+    fn read_event(&mut self) -> Result<Option<XMLEvent>> {
+        if let Some(raw) = &mut self.raw {
+            raw.clear();
+        }
+
+        if let Some(name) = self.pending_end.take() {
+            self.open_elements.pop();
+            return Ok(Some(XMLEvent::EndElement { name }));
+        }
+
+        if !self.started {
+            self.reader.detect()?;
+        }
+        if !self.started && self.reader.fill_buf()?.starts_with(UTF8_BOM) {
+            self.consume(UTF8_BOM.len())?;
+        }
+
+        loop {
+            let Some(byte) = self.peek_byte()? else {
+                if let Some(name) = self.open_elements.last() {
+                    return Err(self.error(format!("element `{name}` is never closed")));
+                }
+                if !self.root_seen {
+                    return Err(self.error("document has no root element"));
+                }
+                return Ok(None);
+            };
+
+            if byte == b'<' {
+                self.consume(1)?;
+                let event = self.read_markup()?;
+                self.started = true;
+                return Ok(Some(event));
+            }
+
+            self.buffer.clear();
+            self.read_until_markup(self.limits.max_text_length)?;
+            self.started = true;
+
+            let text = self.buffer_as_string()?;
+            if self.open_elements.is_empty() {
+                if is_whitespace(&text) {
+                    continue;
+                }
+                return Err(self.error("text found outside of the root element"));
+            }
+
+            return Ok(Some(XMLEvent::Text(self.unescape(&text)?)));
+        }
+    }
+
+    /// Internal method reading any markup, the leading `<` being already consumed.
+    fn read_markup(&mut self) -> Result<XMLEvent> {
+        self.buffer.clear();
+
+        match self.next_byte()? {
+            b'?' => self.read_processing_instruction(),
+            b'!' => match self.next_byte()? {
+                b'-' => {
+                    self.expect(b"-")?;
+                    self.read_until_sequence(b"--", self.limits.max_text_length)?;
+                    self.expect(b">")?;
+                    Ok(XMLEvent::Comment(self.buffer_as_string()?))
+                }
+                b'[' => {
+                    self.expect(b"CDATA[")?;
+                    self.check_in_root("CDATA section")?;
+                    self.read_until_sequence(b"]]>", self.limits.max_text_length)?;
+                    Ok(XMLEvent::CData(self.buffer_as_string()?))
+                }
+                b'D' => {
+                    self.expect(b"OCTYPE")?;
+                    self.read_doctype()
+                }
+                _ => Err(self.error("invalid markup declaration")),
+            },
+            b'/' => self.read_end_tag(),
+            byte => {
+                self.buffer.push(byte);
+                self.read_start_tag()
+            }
+        }
+    }
+
+    /// Internal method reading a start tag, its first byte being already buffered.
+    fn read_start_tag(&mut self) -> Result<XMLEvent> {
+        self.read_tag(false)?;
+
+        let content = self.buffer_as_string()?;
+        let (content, self_closing) = match content.strip_suffix('/') {
+            Some(content) => (content, true),
+            None => (content.as_str(), false),
+        };
+
+        let name_end = content.find(is_whitespace_char).unwrap_or(content.len());
+        let name = &content[..name_end];
+        self.check_name(name, "element")?;
+        if self.open_elements.is_empty() && self.root_seen {
+            return Err(self.error(format!("element `{name}` found after the root element")));
+        }
+
+        if self.open_elements.len() >= self.limits.max_depth {
+            return Err(XMLError::DepthLimitExceeded(self.limits.max_depth));
+        }
+
+        let attributes = self.parse_attributes(&content[name_end..])?;
+
+        self.root_seen = true;
+        self.open_elements.push(name.to_owned());
+        if self_closing {
+            self.pending_end = Some(name.to_owned());
+        }
+
+        Ok(XMLEvent::StartElement {
+            name: name.to_owned(),
+            attributes,
+        })
+    }
+
+    /// Internal method reading an end tag, the leading `</` being already consumed.
+    fn read_end_tag(&mut self) -> Result<XMLEvent> {
+        self.read_tag(false)?;
+
+        let content = self.buffer_as_string()?;
+        let name = content.trim_end_matches(is_whitespace_char);
+
+        match self.open_elements.pop() {
+            Some(expected) if expected == name => Ok(XMLEvent::EndElement { name: expected }),
+            Some(expected) => Err(self.error(format!(
+                "mismatched end tag, expected `</{expected}>` but found `</{name}>`"
+            ))),
+            None => Err(self.error(format!("unexpected end tag `</{name}>`"))),
+        }
+    }
+
+    /// Internal method reading a processing instruction or the XML declaration,
+    /// the leading `<?` being already consumed.
+    fn read_processing_instruction(&mut self) -> Result<XMLEvent> {
+        self.read_until_sequence(b"?>", self.limits.max_text_length)?;
+
+        let content = self.buffer_as_string()?;
+        let (target, data) = match content.find(is_whitespace_char) {
+            Some(index) => (
+                &content[..index],
+                content[index..].trim_start_matches(is_whitespace_char),
+            ),
+            None => (content.as_str(), ""),
+        };
+
+        if target.eq_ignore_ascii_case("xml") {
+            if self.started || target != "xml" {
+                return Err(
+                    self.error("XML declaration is only allowed at the start of the document")
+                );
+            }
+            return self.parse_declaration(data);
+        }
+        self.check_name(target, "processing instruction target")?;
+
+        Ok(XMLEvent::ProcessingInstruction {
+            target: target.to_owned(),
+            data: (!data.is_empty()).then(|| data.to_owned()),
+        })
+    }
+
+    /// Internal method reading a `<!DOCTYPE>` declaration, the leading `<!DOCTYPE` being already consumed.
+    fn read_doctype(&mut self) -> Result<XMLEvent> {
+        if self.root_seen || self.doctype_seen {
+            return Err(self.error("unexpected document type declaration"));
+        }
+        self.doctype_seen = true;
+
+        self.read_tag(true)?;
+
+        let content = self.buffer_as_string()?;
+        self.parse_entity_declarations(&content)?;
+
+        Ok(XMLEvent::DocumentType(
+            content.trim_matches(is_whitespace_char).to_owned(),
+        ))
+    }
+
+    /// Internal method registering the general entities declared in the internal subset
+    /// of a document type declaration.
+    fn parse_entity_declarations(&mut self, doctype: &str) -> Result<()> {
+        let Some(start) = doctype.find('[') else {
+            return Ok(());
+        };
+
+        let mut input = &doctype[start + 1..];
+        while let Some(index) = input.find('<') {
+            input = &input[index..];
+            // Comments and processing instructions may mention declarations, being skipped
+            if let Some(rest) = input.strip_prefix("<!--") {
+                input = rest
+                    .find("-->")
+                    .map_or("", |end| &rest[end + "-->".len()..]);
+                continue;
+            }
+            if let Some(rest) = input.strip_prefix("<?") {
+                input = rest.find("?>").map_or("", |end| &rest[end + "?>".len()..]);
+                continue;
+            }
+            let Some(rest) = input.strip_prefix("<!ENTITY") else {
+                input = &input[1..];
+                continue;
+            };
+            input = rest.trim_start_matches(is_whitespace_char);
+
+            let parameter = input.starts_with('%');
+            if parameter {
+                input = input[1..].trim_start_matches(is_whitespace_char);
+            }
+
+            let name_end = input
+                .find(is_whitespace_char)
+                .ok_or_else(|| self.error("invalid entity declaration"))?;
+            let name = &input[..name_end];
+            self.check_name(name, "entity")?;
+
+            input = input[name_end..].trim_start_matches(is_whitespace_char);
+            let entity = match input.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = input[1..].find(quote).ok_or_else(|| {
+                        self.error(format!("unterminated value for entity `{name}`"))
+                    })?;
+                    let value = &input[1..=end];
+                    input = &input[end + 2..];
+                    Entity::Internal(value.to_owned())
+                }
+                _ if input.starts_with("SYSTEM") || input.starts_with("PUBLIC") => Entity::External,
+                _ => return Err(self.error(format!("invalid declaration of entity `{name}`"))),
+            };
+
+            // Parameter entities are only meaningful inside the document type declaration,
+            // which is not interpreted any further.
+            if !parameter {
+                self.entities.entry(name.to_owned()).or_insert(entity);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Internal method parsing the pseudo-attributes of the XML declaration.
+    fn parse_declaration(&mut self, data: &str) -> Result<XMLEvent> {
+        let mut version = None;
+        let mut encoding = None;
+        let mut standalone = None;
+
+        for (name, value) in self.parse_attributes(data)? {
+            match name.as_str() {
+                "version" => version = Some(value),
+                "encoding" => encoding = Some(value),
+                "standalone" => {
+                    standalone = Some(match value.as_str() {
+                        "yes" => true,
+                        "no" => false,
+                        _ => return Err(self.error(format!("invalid standalone value `{value}`"))),
+                    });
+                }
+                _ => {
+                    return Err(self.error(format!("unknown XML declaration attribute `{name}`")));
+                }
+            }
+        }
+
+        let version = version.ok_or_else(|| self.error("XML declaration has no version"))?;
+
+        Ok(XMLEvent::Declaration {
+            version,
+            encoding,
+            standalone,
+        })
+    }
+
+    /// Internal method parsing the attributes following the name of a start tag.
+    fn parse_attributes(&mut self, mut input: &str) -> Result<Vec<(String, String)>> {
+        let mut attributes: Vec<(String, String)> = Vec::new();
+
+        input = input.trim_start_matches(is_whitespace_char);
+        while !input.is_empty() {
+            let equal = input
+                .find('=')
+                .ok_or_else(|| self.error("attribute without value"))?;
+            let name = input[..equal].trim_end_matches(is_whitespace_char);
+            self.check_name(name, "attribute")?;
+            if attributes.len() >= self.limits.max_attributes {
+                return Err(XMLError::TooManyAttributes(self.limits.max_attributes));
+            }
+
+            input = input[equal + 1..].trim_start_matches(is_whitespace_char);
+            let quote = match input.chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error(format!("unquoted value for attribute `{name}`"))),
+            };
+            let end = input[1..]
+                .find(quote)
+                .ok_or_else(|| self.error(format!("unterminated value for attribute `{name}`")))?;
+            let value = &input[1..=end];
+            if value.contains('<') {
+                return Err(self.error(format!("`<` found in value of attribute `{name}`")));
+            }
+
+            input = &input[end + 2..];
+            if !input.is_empty() && !input.starts_with(is_whitespace_char) {
+                return Err(self.error("missing whitespace between attributes"));
+            }
+            input = input.trim_start_matches(is_whitespace_char);
+
+            if attributes.iter().any(|(k, _)| k == name) {
+                return Err(self.error(format!("duplicate attribute `{name}`")));
+            }
+            attributes.push((name.to_owned(), self.unescape(value)?));
+        }
+
+        Ok(attributes)
+    }
+
+    /// Internal method reading bytes until the next `<` or the end of the document.
+    ///
+    /// The `<` is not consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of bytes to buffer.
+    fn read_until_markup(&mut self, limit: usize) -> Result<()> {
+        loop {
+            let chunk = self.reader.fill_buf()?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+
+            let (used, found) = chunk
+                .iter()
+                .position(|&b| b == b'<')
+                .map_or((chunk.len(), false), |index| (index, true));
+            self.buffer.extend_from_slice(&chunk[..used]);
+            self.consume(used)?;
+            Self::check_length(&self.buffer, limit)?;
+
+            if found {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Internal method reading bytes until the given sequence, which is consumed but not buffered.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The bytes to read until.
+    /// * `limit` - The maximum number of bytes to buffer.
+    fn read_until_sequence(&mut self, sequence: &[u8], limit: usize) -> Result<()> {
+        let last = sequence[sequence.len() - 1];
+
+        loop {
+            let chunk = self.reader.fill_buf()?;
+            if chunk.is_empty() {
+                return Err(self.error("unexpected end of document"));
+            }
+
+            let mut used = chunk.len();
+            let mut found = false;
+            for (index, &byte) in chunk.iter().enumerate() {
+                self.buffer.push(byte);
+                if byte == last && self.buffer.ends_with(sequence) {
+                    used = index + 1;
+                    found = true;
+                    break;
+                }
+            }
+            self.consume(used)?;
+
+            if found {
+                self.buffer.truncate(self.buffer.len() - sequence.len());
+                return Self::check_length(&self.buffer, limit);
+            }
+            Self::check_length(&self.buffer, limit)?;
+        }
+    }
+
+    /// Internal method reading bytes until a closing `>` found outside of quotes,
+    /// which is consumed but not buffered.
+    ///
+    /// Between square brackets, comments and processing instructions are skipped, and
+    /// quotes are only tracked inside declarations.
+    ///
+    /// The limits are enforced on each name and attribute value of start and end tags,
+    /// and on the whole content of document type declarations.
+    ///
+    /// # Arguments
+    ///
+    /// * `brackets` - A boolean indicating whether `>` found between square brackets should be
+    ///   skipped, which is the case for document type declarations.
+    fn read_tag(&mut self, brackets: bool) -> Result<()> {
+        let mut limiter = None;
+        if !brackets {
+            let mut tag = TagLimiter::new(self.limits.clone());
+            // The first byte of start tags is already buffered
+            for &byte in &self.buffer {
+                tag.check(None, byte)?;
+            }
+            limiter = Some(tag);
+        }
+        let limit = if brackets {
+            self.limits.max_text_length
+        } else {
+            usize::MAX
+        };
+        let mut quote = None;
+        let mut depth = 0usize;
+        let mut markup = SubsetMarkup::None;
+
+        loop {
+            let chunk = self.reader.fill_buf()?;
+            if chunk.is_empty() {
+                return Err(self.error("unexpected end of document"));
+            }
+
+            let mut used = chunk.len();
+            let mut found = false;
+            for (index, &byte) in chunk.iter().enumerate() {
+                if let Some(limiter) = &mut limiter {
+                    limiter.check(quote, byte)?;
+                }
+
+                let ends_with = |end: &[u8]| self.buffer.ends_with(end);
+                match (quote, byte) {
+                    (Some(q), _) if q == byte => quote = None,
+                    (Some(_), _) => {}
+                    (None, _) if depth > 0 && markup == SubsetMarkup::Comment => {
+                        if byte == b'>' && ends_with(b"--") {
+                            markup = SubsetMarkup::None;
+                        }
+                    }
+                    (None, _) if depth > 0 && markup == SubsetMarkup::Instruction => {
+                        if byte == b'>' && ends_with(b"?") {
+                            markup = SubsetMarkup::None;
+                        }
+                    }
+                    (None, b'<') if depth > 0 => markup = SubsetMarkup::Declaration,
+                    (None, b'?') if depth > 0 && ends_with(b"<") => {
+                        markup = SubsetMarkup::Instruction;
+                    }
+                    (None, b'-') if depth > 0 && ends_with(b"<!-") => {
+                        markup = SubsetMarkup::Comment;
+                    }
+                    (None, b'>') if depth > 0 => markup = SubsetMarkup::None,
+                    (None, b'"' | b'\'') if depth == 0 || markup == SubsetMarkup::Declaration => {
+                        quote = Some(byte);
+                    }
+                    (None, b'[') if brackets => depth += 1,
+                    (None, b']') if brackets => depth = depth.saturating_sub(1),
+                    (None, b'>') if depth == 0 => {
+                        used = index + 1;
+                        found = true;
+                        break;
+                    }
+                    (None, _) => {}
+                }
+                self.buffer.push(byte);
+            }
+            self.consume(used)?;
+            Self::check_length(&self.buffer, limit)?;
+
+            if found {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Internal method consuming the given bytes, raising an error if they are not found.
+    fn expect(&mut self, expected: &[u8]) -> Result<()> {
+        for &byte in expected {
+            if self.next_byte()? != byte {
+                return Err(self.error(format!("expected `{}`", String::from_utf8_lossy(expected))));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Internal method raising an error if the given buffer is longer than the given limit.
+    const fn check_length(buffer: &[u8], limit: usize) -> Result<()> {
+        if buffer.len() > limit {
+            return Err(XMLError::TextTooLong(limit));
+        }
+
+        Ok(())
+    }
+
+    /// Internal method raising an error if the given name is invalid or too long.
+    fn check_name(&self, name: &str, what: &str) -> Result<()> {
+        if name.len() > self.limits.max_name_length {
+            return Err(XMLError::NameTooLong(self.limits.max_name_length));
+        }
+        if !is_valid_name(name) {
+            return Err(self.error(format!("invalid {what} name `{name}`")));
+        }
+
+        Ok(())
+    }
+
+    /// Internal method raising an error if no element is currently opened.
+    fn check_in_root(&self, what: &str) -> Result<()> {
+        if self.open_elements.is_empty() {
+            return Err(self.error(format!("{what} found outside of the root element")));
+        }
+
+        Ok(())
+    }
+
+    /// Internal method returning the next byte without consuming it.
+    fn peek_byte(&mut self) -> Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    /// Internal method consuming and returning the next byte.
+    fn next_byte(&mut self) -> Result<u8> {
+        let byte = self
+            .peek_byte()?
+            .ok_or_else(|| self.error("unexpected end of document"))?;
+        self.consume(1)?;
+
+        Ok(byte)
+    }
+
+    /// Internal method consuming the given amount of bytes from the source.
+    fn consume(&mut self, amount: usize) -> Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.extend_from_slice(&self.reader.fill_buf()?[..amount]);
+        }
+        self.reader.consume(amount);
+        self.offset += amount;
+
+        Ok(())
+    }
+
+    /// Internal method returning the current buffer as a String.
+    fn buffer_as_string(&self) -> Result<String> {
+        std::str::from_utf8(&self.buffer)
+            .map(str::to_owned)
+            .map_err(|_| self.error("invalid UTF-8 sequence"))
+    }
+
+    /// Internal method resolving entity and character references in the given text.
+    fn unescape(&mut self, text: &str) -> Result<String> {
+        let mut result = String::with_capacity(text.len());
+        self.expanding.clear();
+        self.expand(text, &mut result)?;
+
+        if result.len() > self.limits.max_text_length {
+            return Err(XMLError::TextTooLong(self.limits.max_text_length));
+        }
+
+        Ok(result)
+    }
+
+    /// Internal method expanding the references found in the given text.
+    ///
+    /// Nested entities are expanded iteratively, so that long chains of references cannot
+    /// overflow the stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to expand.
+    /// * `result` - The String the expanded text is appended to.
+    fn expand(&mut self, text: &str, result: &mut String) -> Result<()> {
+        // Texts being expanded along with the offset of their remaining part, and the name
+        // of the entity they are the value of, which is also in `self.expanding`
+        let mut stack: Vec<(Cow<'_, str>, usize, Option<String>)> =
+            vec![(Cow::Borrowed(text), 0, None)];
+
+        while !stack.is_empty() {
+            let depth = stack.len() - 1;
+            let (value, position, _) = &mut stack[depth];
+            let input = &value[*position..];
+            let Some(start) = input.find('&') else {
+                self.push_expanded(input, depth, result)?;
+                if let Some((_, _, Some(name))) = stack.pop() {
+                    self.expanding.remove(&name);
+                }
+                continue;
+            };
+            self.push_expanded(&input[..start], depth, result)?;
+
+            let reference = &input[start + 1..];
+            let end = reference
+                .find(';')
+                .ok_or_else(|| self.error("unterminated entity reference"))?;
+            let name = &reference[..end];
+            *position += start + end + 2;
+
+            if let Some(c) = predefined_entity(name).map_err(|e| self.error(e))? {
+                self.push_expanded(c.encode_utf8(&mut [0; 4]), depth, result)?;
+                continue;
+            }
+
+            let value = match self.entities.get(name) {
+                Some(Entity::Internal(value)) => value.clone(),
+                Some(Entity::External) => return Err(XMLError::ExternalEntity(name.to_owned())),
+                None => return Err(self.error(format!("unknown entity reference `&{name};`"))),
+            };
+            if depth >= self.limits.max_entity_depth {
+                return Err(XMLError::EntityDepthExceeded(self.limits.max_entity_depth));
+            }
+            if self.expanding.contains(name) {
+                return Err(self.error(format!("entity `{name}` references itself")));
+            }
+
+            let name = name.to_owned();
+            self.expanding.insert(name.clone());
+            stack.push((Cow::Owned(value), 0, Some(name)));
+        }
+
+        Ok(())
+    }
+
+    /// Internal method appending text to an expansion result, accounting for the bytes
+    /// produced by entity expansions.
+    fn push_expanded(&mut self, text: &str, depth: usize, result: &mut String) -> Result<()> {
+        if depth > 0 {
+            self.expanded = self.expanded.saturating_add(text.len());
+            if self.expanded > self.limits.max_entity_expansion {
+                return Err(XMLError::EntityExpansionExceeded(
+                    self.limits.max_entity_expansion,
+                ));
+            }
+        }
+        result.push_str(text);
+
+        Ok(())
+    }
+
+    /// Internal method building a parsing error located at the current offset.
+    fn error(&self, message: impl Display) -> XMLError {
+        XMLError::ParseError(format!("{message} at byte {}", self.offset))
+    }
+}
+
+impl<R: BufRead> Iterator for XMLReader<R> {
+    type Item = Result<XMLEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::BufRead;
+
+use crate::treebuilder::TreeBuilder;
+use crate::{Result, XMLElement, XMLError, XMLEvent, XMLLimits, XMLReader};
+
+/// Streaming splitter yielding the elements of a document matching a path, one at a time.
+///
+/// Everything outside of the matching elements is discarded while reading, so that
+/// documents holding millions of records are processed with a constant memory usage.
+///
+/// Each yielded record holds the namespace declarations in scope from its ancestors,
+/// so that it can be processed on its own.
+pub struct XMLSplitter<R: BufRead> {
+    /// The reader the document is read from.
+    reader: XMLReader<R>,
+
+    /// The names of the elements composing the path, `*` matching any name.
+    path: Vec<String>,
+
+    /// Namespace declarations of the currently opened elements matching the start of the path.
+    namespaces: Vec<Vec<(String, String)>>,
+}
+
+impl<R: BufRead> XMLSplitter<R> {
+    /// Instantiates a new `XMLSplitter` yielding the elements matching the given path.
+    ///
+    /// Only entity expansion is limited, by `XMLLimits::trusted()`.
+    ///
+    /// Raises `XMLError` if the path is not an absolute path such as `/catalog/product`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - An object implementing `BufRead` to read the document from.
+    /// * `path` - A string slice that holds the absolute path of the records, `*` matching any name.
+    pub fn new(reader: R, path: &str) -> Result<Self> {
+        Self::with_limits(reader, path, XMLLimits::trusted())
+    }
+
+    /// Instantiates a new `XMLSplitter` enforcing the given limits, suited for untrusted documents.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - An object implementing `BufRead` to read the document from.
+    /// * `path` - A string slice that holds the absolute path of the records, `*` matching any name.
+    /// * `limits` - The `XMLLimits` to enforce while reading.
+    pub fn with_limits(reader: R, path: &str, limits: XMLLimits) -> Result<Self> {
+        let path: Vec<String> = path
+            .strip_prefix('/')
+            .ok_or_else(|| XMLError::ParseError(format!("record path `{path}` is not absolute")))?
+            .split('/')
+            .map(str::to_owned)
+            .collect();
+
+        if path.iter().any(String::is_empty) {
+            return Err(XMLError::ParseError(
+                "record path contains an empty element name".into(),
+            ));
+        }
+
+        Ok(Self {
+            reader: XMLReader::with_limits(reader, limits),
+            path,
+            namespaces: Vec::new(),
+        })
+    }
+
+    /// Reads the document until the next record, and returns it.
+    ///
+    /// Returns `None` once the whole document has been read.
+    ///
+    /// Raises `XMLError` if the document is not well-formed or cannot be read. A record
+    /// mixing text and elements, or exceeding the limits, raises `XMLError` as well, while
+    /// the next call resumes at the following record.
+    pub fn next_record(&mut self) -> Result<Option<XMLElement>> {
+        while let Some(event) = self.reader.next_event()? {
+            match event {
+                XMLEvent::StartElement { name, attributes } => {
+                    let depth = self.reader.depth();
+
+                    // Only elements whose ancestors all match the path are considered
+                    if self.namespaces.len() + 1 != depth
+                        || self.path[depth - 1] != "*" && self.path[depth - 1] != name
+                    {
+                        continue;
+                    }
+
+                    if depth < self.path.len() {
+                        self.namespaces.push(
+                            attributes
+                                .into_iter()
+                                .filter(|(key, _)| is_namespace_declaration(key))
+                                .collect(),
+                        );
+                    } else {
+                        return self.read_record(name, attributes).map(Some);
+                    }
+                }
+                XMLEvent::EndElement { .. } if self.reader.depth() < self.namespaces.len() => {
+                    self.namespaces.pop();
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Internal method reading a whole record, its start tag being already read.
+    fn read_record(
+        &mut self,
+        name: String,
+        mut attributes: Vec<(String, String)>,
+    ) -> Result<XMLElement> {
+        // Declarations of the closest ancestors take precedence
+        let mut inherited: Vec<(String, String)> = Vec::new();
+        for (key, value) in self.namespaces.iter().flatten() {
+            if attributes.iter().any(|(k, _)| k == key) {
+                continue;
+            }
+            match inherited.iter_mut().find(|(k, _)| k == key) {
+                Some((_, inherited_value)) => inherited_value.clone_from(value),
+                None => inherited.push((key.clone(), value.clone())),
+            }
+        }
+        inherited.append(&mut attributes);
+
+        let depth = self.reader.depth();
+        let mut tree = TreeBuilder::new(self.reader.limits());
+        let mut event = XMLEvent::StartElement {
+            name,
+            attributes: inherited,
+        };
+        loop {
+            match tree.push(event) {
+                Ok(Some(record)) => return Ok(record),
+                Ok(None) => {}
+                Err(e) => {
+                    self.skip_record(depth)?;
+                    return Err(e);
+                }
+            }
+
+            event = self
+                .reader
+                .next_event()?
+                .ok_or_else(|| XMLError::ParseError("unexpected end of document".into()))?;
+        }
+    }
+
+    /// Internal method skipping the rest of the record opened at the given depth.
+    fn skip_record(&mut self, depth: usize) -> Result<()> {
+        while self.reader.depth() >= depth {
+            if self.reader.next_event()?.is_none() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for XMLSplitter<R> {
+    type Item = Result<XMLElement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Returns whether the given attribute name is a namespace declaration.
+fn is_namespace_declaration(name: &str) -> bool {
+    name == "xmlns" || name.starts_with("xmlns:")
+}
@@ -0,0 +1,169 @@
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A string storing XML names and attribute values.
+///
+/// Static strings are borrowed and shared strings are reference-counted, so that neither
+/// allocates when used by many elements. Owned strings are kept as is.
+#[derive(Clone)]
+pub struct XMLStr(Repr);
+
+/// Internal storage of a `XMLStr`.
+#[derive(Clone)]
+enum Repr {
+    /// A string borrowed for the whole program.
+    Static(&'static str),
+
+    /// A reference-counted string, cloned without allocating.
+    Shared(Arc<str>),
+
+    /// An owned string.
+    Owned(String),
+}
+
+impl XMLStr {
+    /// Instantiates a reference-counted `XMLStr` from the given string slice.
+    ///
+    /// The string is copied once, clones of the returned object sharing it afterwards.
+    /// It allows interning names and values read from other buffers.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A string slice that holds the value to share.
+    #[must_use]
+    pub fn shared(value: &str) -> Self {
+        Self(Repr::Shared(value.into()))
+    }
+
+    /// Internal method instantiating a `XMLStr` borrowing the given static string, in
+    /// constant contexts.
+    pub(crate) const fn from_static(value: &'static str) -> Self {
+        Self(Repr::Static(value))
+    }
+
+    /// Returns the `XMLStr` as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Static(value) => value,
+            Repr::Shared(value) => value,
+            Repr::Owned(value) => value,
+        }
+    }
+}
+
+impl Deref for XMLStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for XMLStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for XMLStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&'static str> for XMLStr {
+    fn from(value: &'static str) -> Self {
+        Self(Repr::Static(value))
+    }
+}
+
+impl From<String> for XMLStr {
+    fn from(value: String) -> Self {
+        Self(Repr::Owned(value))
+    }
+}
+
+impl From<Arc<str>> for XMLStr {
+    fn from(value: Arc<str>) -> Self {
+        Self(Repr::Shared(value))
+    }
+}
+
+impl From<Cow<'static, str>> for XMLStr {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(value) => value.into(),
+            Cow::Owned(value) => value.into(),
+        }
+    }
+}
+
+impl From<XMLStr> for String {
+    fn from(value: XMLStr) -> Self {
+        match value.0 {
+            Repr::Owned(value) => value,
+            _ => value.as_str().to_owned(),
+        }
+    }
+}
+
+impl PartialEq for XMLStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for XMLStr {}
+
+impl PartialEq<str> for XMLStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for XMLStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for XMLStr {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialOrd for XMLStr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for XMLStr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for XMLStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl fmt::Display for XMLStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for XMLStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,63 @@
+use std::io::Write;
+
+use crate::{Result, XML, XMLElement, XMLError, XMLWriter};
+
+/// Open-ended document whose root element stays opened, as used by stream protocols
+/// such as XMPP.
+///
+/// The XML declaration and the root start tag are written when the stream is opened,
+/// then each stanza is written and flushed as soon as it is sent. The root end tag is
+/// only written when the stream is closed.
+///
+/// As with `XMLWriter`, the root element is never kept on a single line, while stanzas
+/// are laid out as by `XML::generate`.
+pub struct XMLStream<W: Write> {
+    /// The writer the stream is written to.
+    writer: XMLWriter<W>,
+
+    /// The name of the root element.
+    root: String,
+}
+
+impl<W: Write> XMLStream<W> {
+    /// Internal method opening a new stream, writing the root start tag at once.
+    pub(crate) fn open(document: XML, writer: W, root: &XMLElement) -> Result<Self> {
+        if !root.children().is_empty() || root.text().is_some() {
+            return Err(XMLError::WriterError(format!(
+                "stream root element `{}` cannot have content",
+                root.name()
+            )));
+        }
+
+        let mut writer = XMLWriter::new(document, writer);
+        writer.open_element(root)?;
+        writer.flush()?;
+
+        Ok(Self {
+            writer,
+            root: root.name().to_owned(),
+        })
+    }
+
+    /// Writes the given stanza as a child of the root element, and flushes it.
+    ///
+    /// Raises `XMLError` if the stanza cannot be written.
+    ///
+    /// # Arguments
+    ///
+    /// * `stanza` - The `XMLElement` to send.
+    pub fn send(&mut self, stanza: &XMLElement) -> Result<()> {
+        self.writer.write_element(stanza)?;
+
+        self.writer.flush()
+    }
+
+    /// Closes the stream by writing the root end tag, and returns the underlying writer.
+    ///
+    /// Raises `XMLError` if the end tag cannot be written.
+    pub fn close(mut self) -> Result<W> {
+        self.writer.end_element(&self.root)?;
+
+        self.writer.finish()
+    }
+}
@@ -0,0 +1,89 @@
+use crate::utils::is_whitespace_char;
+
+/// Formatting of an attribute, recorded by lossless parsing.
+#[derive(Clone)]
+pub struct AttributeTrivia {
+    /// Whitespaces preceding the attribute name.
+    pub prefix: String,
+
+    /// Raw content between the attribute name and its opening quote, `=` included.
+    pub separator: String,
+
+    /// The quote character surrounding the attribute value.
+    pub quote: char,
+}
+
+/// Formatting trivia of an `XMLElement`, recorded by lossless parsing and honored when rendering.
+#[derive(Clone, Default)]
+pub struct XMLTrivia {
+    /// Formatting of each attribute, in the same order as the element attributes.
+    ///
+    /// Attributes added afterwards are rendered with the default formatting.
+    pub attributes: Vec<AttributeTrivia>,
+
+    /// Whitespaces between the last attribute and the end of the start tag.
+    pub start_tag_end: String,
+
+    /// Whether the element was written as a self-closing tag.
+    pub self_closing: bool,
+
+    /// Raw content found before each child element, and finally before the end tag.
+    ///
+    /// Always holds one more item than the element has children.
+    pub gaps: Vec<String>,
+
+    /// Whitespaces between the element name and the end of the end tag.
+    pub end_tag_end: String,
+}
+
+impl XMLTrivia {
+    /// Parses the trivia and the raw attributes of a start tag, given without its `<` and `>`.
+    ///
+    /// The tag is expected to have been checked by an `XMLReader` beforehand.
+    pub fn from_start_tag(tag: &str) -> (Self, Vec<(String, String)>) {
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+
+        let mut trivia = Self {
+            self_closing,
+            ..Self::default()
+        };
+        let mut attributes = Vec::new();
+
+        let mut input = &tag[tag.find(is_whitespace_char).unwrap_or(tag.len())..];
+        loop {
+            let trimmed = input.trim_start_matches(is_whitespace_char);
+            if trimmed.is_empty() {
+                trivia.start_tag_end = input.to_owned();
+                break;
+            }
+            let prefix = &input[..input.len() - trimmed.len()];
+
+            let name_end = trimmed
+                .find(|c| c == '=' || is_whitespace_char(c))
+                .unwrap_or(trimmed.len());
+            let (name, input_after_name) = trimmed.split_at(name_end);
+
+            let quote_index = input_after_name.find(['"', '\'']).unwrap_or(0);
+            let quote = input_after_name[quote_index..]
+                .chars()
+                .next()
+                .unwrap_or('"');
+            let value = &input_after_name[quote_index + 1..];
+            let value_end = value.find(quote).unwrap_or(value.len());
+
+            trivia.attributes.push(AttributeTrivia {
+                prefix: prefix.to_owned(),
+                separator: input_after_name[..quote_index].to_owned(),
+                quote,
+            });
+            attributes.push((name.to_owned(), value[..value_end].to_owned()));
+
+            input = value.get(value_end + 1..).unwrap_or_default();
+        }
+
+        (trivia, attributes)
+    }
+}
@@ -0,0 +1,580 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use crate::renderer::{write_attributes, write_indent};
+use crate::transcoding::EncodingWriter;
+use crate::utils::is_valid_name;
+use crate::{Result, XML, XMLElement, XMLError, XMLStr, escape_str};
+
+/// Kind of content written so far inside an opened element.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Content {
+    Empty,
+    Text,
+    Elements,
+}
+
+/// An element whose start tag has been written, but not its end tag.
+struct OpenElement {
+    name: String,
+    content: Content,
+}
+
+/// Streaming writer generating a XML document without holding it in memory.
+///
+/// The document is written event after event, while checking that the sequence of
+/// calls produces a well-formed document. Formatting options are the ones of the
+/// `XMLBuilder` it is built from.
+///
+/// Elements written with `write_element` are laid out as by `XML::generate`. Elements
+/// written event by event cannot be measured beforehand, so that they are never kept on
+/// a single line by `inline_width`, and their text is neither wrapped nor re-indented.
+///
+/// As with `XMLElement`, an element holds either text or child nodes, but not both.
+pub struct XMLWriter<W: Write> {
+    /// The document holding the declaration and formatting options.
+    document: XML,
+
+    /// The writer the document is written to.
+    writer: EncodingWriter<W>,
+
+    /// The currently opened elements, the innermost being the last one.
+    elements: Vec<OpenElement>,
+
+    /// The attributes of the innermost element, while its start tag is not written yet.
+    attributes: Option<Vec<(XMLStr, XMLStr)>>,
+
+    /// Whether the XML declaration has been written.
+    started: bool,
+
+    /// Whether the root element has been closed.
+    root_closed: bool,
+}
+
+impl<W: Write> XMLWriter<W> {
+    /// Internal method instantiating a new `XMLWriter` from the given document options.
+    pub(crate) const fn new(document: XML, writer: W) -> Self {
+        let writer = EncodingWriter::new(writer, document.output_encoding);
+
+        Self {
+            document,
+            writer,
+            elements: Vec::new(),
+            attributes: None,
+            started: false,
+            root_closed: false,
+        }
+    }
+
+    /// Returns the number of currently opened elements.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Writes the start tag of a new element.
+    ///
+    /// Attributes can be added with `attribute` until any content is written.
+    ///
+    /// Raises `XMLError` if the name is not a valid XML name, if the root element is
+    /// already closed, or if the current element holds text.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the element.
+    pub fn start_element(&mut self, name: &str) -> Result<()> {
+        check_name(name)?;
+        if self.root_closed {
+            return Err(XMLError::WriterError(format!(
+                "cannot write element `{name}` after the root element"
+            )));
+        }
+        if let Some(max_depth) = self.document.options.max_depth
+            && self.elements.len() >= max_depth
+        {
+            return Err(XMLError::DepthLimitExceeded(max_depth));
+        }
+        self.open_child()?;
+
+        self.elements.push(OpenElement {
+            name: name.to_owned(),
+            content: Content::Empty,
+        });
+        self.attributes = Some(Vec::new());
+
+        Ok(())
+    }
+
+    /// Adds an attribute to the element whose start tag is being written.
+    ///
+    /// The value is escaped.
+    ///
+    /// Raises `XMLError` if no start tag is being written, if the name is not a valid
+    /// XML name, or if the attribute is already set.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the attribute.
+    /// * `value` - A string slice that holds the value of the attribute.
+    pub fn attribute(&mut self, name: &str, value: &str) -> Result<()> {
+        check_name(name)?;
+        let Some(attributes) = &mut self.attributes else {
+            return Err(XMLError::WriterError(format!(
+                "attribute `{name}` must directly follow a start tag"
+            )));
+        };
+        if attributes.iter().any(|(key, _)| key == name) {
+            return Err(XMLError::WriterError(format!(
+                "attribute `{name}` is already set"
+            )));
+        }
+
+        attributes.push((name.to_owned().into(), escape_str(value).into()));
+
+        Ok(())
+    }
+
+    /// Writes text inside the current element.
+    ///
+    /// The text is escaped, and can be written in several calls. It is written as is,
+    /// without being wrapped nor re-indented.
+    ///
+    /// Raises `XMLError` if no element is opened, or if the current element holds
+    /// child nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - A string slice that holds the text to write.
+    pub fn text(&mut self, text: &str) -> Result<()> {
+        self.open_text()?;
+        write!(self.writer, "{}", escape_str(text))?;
+
+        Ok(())
+    }
+
+    /// Writes a CDATA section inside the current element.
+    ///
+    /// Raises `XMLError` if no element is opened, if the current element holds child
+    /// nodes, or if the content contains `]]>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - A string slice that holds the content of the section.
+    pub fn cdata(&mut self, content: &str) -> Result<()> {
+        if content.contains("]]>") {
+            return Err(XMLError::WriterError(
+                "CDATA section cannot contain `]]>`".into(),
+            ));
+        }
+        self.open_text()?;
+        write!(self.writer, "<![CDATA[{content}]]>")?;
+
+        Ok(())
+    }
+
+    /// Writes a comment, either inside the current element or outside of the root one.
+    ///
+    /// Raises `XMLError` if the comment contains `--` or ends with `-`, or if the current
+    /// element holds text.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - A string slice that holds the content of the comment.
+    pub fn comment(&mut self, comment: &str) -> Result<()> {
+        if comment.contains("--") || comment.ends_with('-') {
+            return Err(XMLError::WriterError(format!(
+                "comment `{comment}` cannot contain `--` nor end with `-`"
+            )));
+        }
+
+        self.write_node(&format!("<!--{comment}-->"))
+    }
+
+    /// Writes a processing instruction, either inside the current element or outside of
+    /// the root one.
+    ///
+    /// Raises `XMLError` if the target is not a valid name or is reserved, if the data
+    /// contains `?>`, or if the current element holds text.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - A string slice that holds the target of the instruction.
+    /// * `data` - An optional string slice that holds the data of the instruction.
+    pub fn processing_instruction(&mut self, target: &str, data: Option<&str>) -> Result<()> {
+        check_name(target)?;
+        if target.eq_ignore_ascii_case("xml") {
+            return Err(XMLError::WriterError(format!(
+                "processing instruction target `{target}` is reserved"
+            )));
+        }
+
+        match data {
+            Some(data) if data.contains("?>") => Err(XMLError::WriterError(
+                "processing instruction data cannot contain `?>`".into(),
+            )),
+            Some(data) => self.write_node(&format!("<?{target} {data}?>")),
+            None => self.write_node(&format!("<?{target}?>")),
+        }
+    }
+
+    /// Writes a whole `XMLElement` as a child of the current element, or as root element.
+    ///
+    /// Raises `XMLError` if the root element is already closed, or if the current element
+    /// holds text.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The `XMLElement` to write.
+    pub fn write_element(&mut self, element: &XMLElement) -> Result<()> {
+        if self.root_closed {
+            return Err(XMLError::WriterError(format!(
+                "cannot write element `{}` after the root element",
+                element.name()
+            )));
+        }
+        self.open_child()?;
+
+        element.render_level(
+            &mut self.writer,
+            self.elements.len(),
+            &self.document.options,
+        )?;
+        self.root_closed = self.elements.is_empty();
+
+        Ok(())
+    }
+
+    /// Writes the end tag of the current element.
+    ///
+    /// Raises `XMLError` if no element is opened, or if the given name does not match
+    /// the one of the current element.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the element to close.
+    pub fn end_element(&mut self, name: &str) -> Result<()> {
+        let Some(element) = self.elements.last() else {
+            return Err(XMLError::WriterError(format!(
+                "end tag `{name}` does not match any opened element"
+            )));
+        };
+        if element.name != name {
+            return Err(XMLError::WriterError(format!(
+                "end tag `{name}` does not match the opened element `{}`",
+                element.name
+            )));
+        }
+
+        let suffix = self.suffix();
+        if self.attributes.is_some() {
+            self.write_start_tag(false)?;
+            if self.document.options.expand_empty_tags {
+                write!(self.writer, "></{name}>{suffix}")?;
+            } else {
+                write!(self.writer, " />{suffix}")?;
+            }
+        } else if element.content == Content::Text {
+            write!(self.writer, "</{name}>{suffix}")?;
+        } else {
+            self.write_indent(self.elements.len() - 1)?;
+            write!(self.writer, "</{name}>{suffix}")?;
+        }
+
+        self.elements.pop();
+        self.root_closed = self.elements.is_empty();
+
+        Ok(())
+    }
+
+    /// Writes a whole element, its content being written by the given closure.
+    ///
+    /// The end tag is written once the closure returns, even if a call made inside it
+    /// failed, so that start and end tags are always balanced. Errors raised inside the
+    /// closure are deferred: the remaining calls are ignored, and the first error is
+    /// returned once the element is closed.
+    ///
+    /// Raises `XMLError` if the name is not a valid XML name, if the root element is
+    /// already closed, or if any call made inside the closure fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the element.
+    /// * `content` - A closure writing the attributes and content of the element.
+    pub fn element<F>(&mut self, name: &str, content: F) -> Result<()>
+    where
+        F: FnOnce(&mut XMLScope<'_, W>),
+    {
+        self.start_element(name)?;
+
+        let mut scope = XMLScope {
+            writer: self,
+            error: None,
+        };
+        content(&mut scope);
+        let error = scope.error;
+
+        let end = self.end_element(name);
+        match error {
+            Some(error) => Err(error),
+            None => end,
+        }
+    }
+
+    /// Internal method writing the start tag of the given element, ignoring its content.
+    ///
+    /// The start tag is written at once, instead of waiting for the first child node.
+    pub(crate) fn open_element(&mut self, element: &XMLElement) -> Result<()> {
+        self.start_element(element.name())?;
+        self.attributes = Some(element.attributes().to_vec());
+
+        self.open_child()
+    }
+
+    /// Flushes the underlying writer.
+    ///
+    /// Raises `XMLError` if the writer cannot be flushed.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Ends the document, flushes the underlying writer and returns it.
+    ///
+    /// Raises `XMLError` if the document has no root element, or if some elements are
+    /// still opened.
+    pub fn finish(mut self) -> Result<W> {
+        if let Some(element) = self.elements.last() {
+            return Err(XMLError::WriterError(format!(
+                "element `{}` is not closed",
+                element.name
+            )));
+        }
+        if !self.root_closed {
+            return Err(XMLError::WriterError("document has no root element".into()));
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.writer.into_inner())
+    }
+
+    /// Internal method preparing the current element to receive a child node, writing
+    /// the XML declaration or the pending start tag if needed.
+    fn open_child(&mut self) -> Result<()> {
+        self.start()?;
+
+        if let Some(element) = self.elements.last_mut() {
+            if element.content == Content::Text {
+                return Err(XMLError::InsertError(
+                    "Cannot insert child inside an element with text".into(),
+                ));
+            }
+            element.content = Content::Elements;
+        }
+        if self.attributes.is_some() {
+            self.write_start_tag(true)?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method preparing the current element to receive text, writing the
+    /// pending start tag if needed.
+    fn open_text(&mut self) -> Result<()> {
+        let Some(element) = self.elements.last_mut() else {
+            return Err(XMLError::WriterError(
+                "text cannot be written outside of the root element".into(),
+            ));
+        };
+        if element.content == Content::Elements {
+            return Err(XMLError::InsertError(
+                "Cannot insert text in a non-empty element".into(),
+            ));
+        }
+        element.content = Content::Text;
+
+        if self.attributes.is_some() {
+            self.write_start_tag(false)?;
+            write!(self.writer, ">")?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing a comment or processing instruction on its own line.
+    fn write_node(&mut self, node: &str) -> Result<()> {
+        self.open_child()?;
+
+        self.write_indent(self.elements.len())?;
+        let suffix = self.suffix();
+        write!(self.writer, "{node}{suffix}")?;
+
+        Ok(())
+    }
+
+    /// Internal method writing the XML declaration, if not written yet.
+    fn start(&mut self) -> Result<()> {
+        if !self.started {
+            self.document.write_prolog(&mut self.writer)?;
+            self.started = true;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing the pending start tag of the current element, without
+    /// its closing bracket unless `close` is set.
+    fn write_start_tag(&mut self, close: bool) -> Result<()> {
+        let Some(attributes) = self.attributes.take() else {
+            return Ok(());
+        };
+        let level = self.elements.len() - 1;
+        self.write_indent(level)?;
+        let name = &self.elements[level].name;
+        write!(self.writer, "<{name}")?;
+
+        let options = &self.document.options;
+        let separator = if options.break_lines {
+            // Empty elements are closed by the caller, unless expanded
+            let end = if close
+                || options.expand_empty_tags
+                || self.elements[level].content == Content::Text
+            {
+                ">"
+            } else {
+                " />"
+            };
+            options.attribute_separator(name, &attributes, level, options.indent, end)
+        } else {
+            None
+        };
+        write_attributes(
+            &mut self.writer,
+            &attributes,
+            options.sort_attributes,
+            separator.as_deref(),
+        )?;
+
+        if close {
+            let suffix = self.suffix();
+            write!(self.writer, ">{suffix}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method writing the indentation of the given level, if enabled.
+    fn write_indent(&mut self, level: usize) -> Result<()> {
+        if self.document.options.indent {
+            write_indent(&mut self.writer, &self.document.options.indent_unit, level)?;
+        }
+
+        Ok(())
+    }
+
+    /// Internal method returning the line break written after nodes.
+    const fn suffix(&self) -> &'static str {
+        if self.document.options.break_lines {
+            self.document.options.line_ending.as_str()
+        } else {
+            ""
+        }
+    }
+}
+
+/// Scope of an element being written by `XMLWriter::element`.
+///
+/// Calls do not return errors, the first one being returned by `XMLWriter::element`
+/// once the whole element is written.
+pub struct XMLScope<'a, W: Write> {
+    /// The writer the element is written to.
+    writer: &'a mut XMLWriter<W>,
+
+    /// The first error raised inside this scope.
+    error: Option<XMLError>,
+}
+
+impl<W: Write> XMLScope<'_, W> {
+    /// Adds an attribute to the element. Must be called before any content is written.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the attribute.
+    /// * `value` - The value of the attribute, escaped once formatted.
+    pub fn attr<V: Display>(&mut self, name: &str, value: V) {
+        self.apply(|writer| writer.attribute(name, &value.to_string()));
+    }
+
+    /// Writes a child element, its content being written by the given closure.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the child element.
+    /// * `content` - A closure writing the attributes and content of the child element.
+    pub fn element<F>(&mut self, name: &str, content: F)
+    where
+        F: FnOnce(&mut XMLScope<'_, W>),
+    {
+        self.apply(|writer| writer.element(name, content));
+    }
+
+    /// Writes a whole `XMLElement` as a child of the element.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The `XMLElement` to write.
+    pub fn write_element(&mut self, element: &XMLElement) {
+        self.apply(|writer| writer.write_element(element));
+    }
+
+    /// Writes escaped text inside the element.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - A string slice that holds the text to write.
+    pub fn text(&mut self, text: &str) {
+        self.apply(|writer| writer.text(text));
+    }
+
+    /// Writes a CDATA section inside the element.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - A string slice that holds the content of the section.
+    pub fn cdata(&mut self, content: &str) {
+        self.apply(|writer| writer.cdata(content));
+    }
+
+    /// Writes a comment inside the element.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - A string slice that holds the content of the comment.
+    pub fn comment(&mut self, comment: &str) {
+        self.apply(|writer| writer.comment(comment));
+    }
+
+    /// Internal method running the given call, unless a previous one failed.
+    fn apply<F>(&mut self, call: F)
+    where
+        F: FnOnce(&mut XMLWriter<W>) -> Result<()>,
+    {
+        if self.error.is_none()
+            && let Err(error) = call(self.writer)
+        {
+            self.error = Some(error);
+        }
+    }
+}
+
+/// Returns an error if the given string is not a valid XML name.
+fn check_name(name: &str) -> Result<()> {
+    if is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(XMLError::WriterError(format!(
+            "`{name}` is not a valid name"
+        )))
+    }
+}
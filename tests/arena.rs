@@ -0,0 +1,150 @@
+mod common;
+
+use common::{house, render};
+use xml_builder::{RenderOptions, XMLArena, XMLBuilder, XMLElement, XMLEncoding, XMLError};
+
+fn render_element(element: &XMLElement) -> String {
+    render(element, &RenderOptions::pretty().sort_attributes(true))
+}
+
+fn render_arena(arena: &XMLArena) -> String {
+    let mut writer: Vec<u8> = Vec::new();
+    arena
+        .render(
+            arena.root().unwrap(),
+            &mut writer,
+            &RenderOptions::pretty().sort_attributes(true),
+        )
+        .unwrap();
+
+    String::from_utf8(writer).unwrap()
+}
+
+#[test]
+fn test_arena_building() {
+    let mut arena = XMLArena::with_capacity(3);
+
+    let house = arena.new_element("house");
+    arena.add_attribute(house, "rooms", "2");
+    arena.add_attribute_str(house, "owner", String::from("Zoë"));
+    arena.set_root(house).unwrap();
+    let room = arena.new_element_from_name("room");
+    arena.add_text(room, "Kitchen".into()).unwrap();
+    arena.add_child(house, room).unwrap();
+    let garden = arena.new_element("garden");
+    arena.add_child(house, garden).unwrap();
+
+    assert_eq!(arena.len(), 3, "Both values does not match...");
+    assert_eq!(
+        arena.children(house).count(),
+        2,
+        "Both values does not match..."
+    );
+    assert_eq!(
+        arena.parent(garden),
+        Some(house),
+        "Both values does not match..."
+    );
+    assert_eq!(
+        render_arena(&arena),
+        render_element(&common::house()),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_arena_conversion() {
+    let element = house();
+    let arena = XMLArena::from(&element);
+
+    assert_eq!(
+        render_arena(&arena),
+        render_element(&element),
+        "Both values does not match..."
+    );
+
+    let converted = arena.to_element(arena.root().unwrap());
+    assert_eq!(
+        render_element(&converted),
+        render_element(&element),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_arena_overrides() {
+    let mut element = house();
+    element.disable_line_breaks();
+    let arena = XMLArena::from(&element);
+
+    let expected = "<house owner=\"Zoë\" rooms=\"2\"><room>Kitchen</room><garden /></house>\n";
+    assert_eq!(
+        render_arena(&arena),
+        expected,
+        "Both values does not match..."
+    );
+    assert_eq!(
+        render_element(&arena.to_element(arena.root().unwrap())),
+        expected,
+        "Both values does not match..."
+    );
+
+    let mut arena = XMLArena::new();
+    let root = arena.new_element("house");
+    arena.set_root(root).unwrap();
+    let garden = arena.new_element("garden");
+    arena.enable_empty_tags_expansion(garden);
+    arena.add_child(root, garden).unwrap();
+    assert_eq!(
+        render_arena(&arena),
+        "<house>\n\t<garden></garden>\n</house>\n",
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_arena_document() {
+    let element = house();
+    let arena = XMLArena::from(&element);
+
+    let mut xml = XMLBuilder::new()
+        .sort_attributes(true)
+        .output_encoding(XMLEncoding::UTF16LE)
+        .build();
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate_arena(&arena, &mut writer).unwrap();
+    assert_eq!(
+        xml.rendered_len_arena(&arena).unwrap(),
+        writer.len(),
+        "Both values does not match..."
+    );
+
+    xml.set_root_element(element);
+    let mut expected: Vec<u8> = Vec::new();
+    xml.generate(&mut expected).unwrap();
+    assert_eq!(writer, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_arena_errors() {
+    let mut arena = XMLArena::new();
+
+    let parent = arena.new_element("parent");
+    let child = arena.new_element("child");
+    arena.add_child(parent, child).unwrap();
+
+    let res = arena.add_child(child, parent);
+    assert!(matches!(res, Err(XMLError::InsertError(_))));
+
+    let res = arena.add_child(parent, child);
+    assert!(matches!(res, Err(XMLError::InsertError(_))));
+
+    let res = arena.add_text(parent, "text".into());
+    assert!(matches!(res, Err(XMLError::InsertError(_))));
+
+    let text = arena.new_element("text");
+    arena.add_text(text, "text".into()).unwrap();
+    let other = arena.new_element("child");
+    let res = arena.add_child(text, other);
+    assert!(matches!(res, Err(XMLError::InsertError(_))));
+}
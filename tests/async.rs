@@ -0,0 +1,50 @@
+#![cfg(feature = "tokio")]
+
+use xml_builder::{RenderOptions, XML, XMLBuilder, XMLElement};
+
+fn document() -> XML {
+    let mut xml = XMLBuilder::new().sort_attributes(true).build();
+
+    let mut rows = XMLElement::new("rows");
+    for index in 0..1000 {
+        let mut row = XMLElement::new("row");
+        row.add_attribute("id", &index.to_string());
+        row.add_attribute("active", "true");
+        row.add_text(format!("Row number {index}")).unwrap();
+        rows.add_child(row).unwrap();
+    }
+    rows.add_child(XMLElement::new("end")).unwrap();
+    xml.set_root_element(rows);
+
+    xml
+}
+
+#[tokio::test]
+async fn test_generate_async() {
+    let mut expected: Vec<u8> = Vec::new();
+    document().generate(&mut expected).unwrap();
+
+    let mut writer: Vec<u8> = Vec::new();
+    document().generate_async(&mut writer).await.unwrap();
+
+    assert_eq!(writer, expected, "Both values does not match...");
+}
+
+#[tokio::test]
+async fn test_render_async() {
+    let xml = document();
+    let root = xml.root_element().unwrap();
+
+    let options = RenderOptions::pretty()
+        .sort_attributes(true)
+        .indent(false)
+        .expand_empty_tags(true);
+
+    let mut expected: Vec<u8> = Vec::new();
+    root.render(&mut expected, &options).unwrap();
+
+    let mut writer: Vec<u8> = Vec::new();
+    root.render_async(&mut writer, &options).await.unwrap();
+
+    assert_eq!(writer, expected, "Both values does not match...");
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "stream")]
+
+use futures::StreamExt;
+use futures::executor::block_on;
+use xml_builder::{XML, XMLBuilder, XMLElement, XMLError};
+
+fn row(index: usize) -> XMLElement {
+    let mut row = XMLElement::new("row");
+    row.add_attribute("id", &index.to_string());
+    row.add_text(format!("Row number {index}")).unwrap();
+
+    row
+}
+
+fn document(rows: usize) -> XML {
+    let mut xml = XMLBuilder::new().build();
+
+    let mut root = XMLElement::new("rows");
+    for index in 0..rows {
+        root.add_child(row(index)).unwrap();
+    }
+    xml.set_root_element(root);
+
+    xml
+}
+
+#[test]
+fn test_stream_chunks() {
+    let mut expected: Vec<u8> = Vec::new();
+    document(1000).generate(&mut expected).unwrap();
+
+    let chunks: Vec<_> = block_on(
+        document(1000)
+            .into_stream()
+            .unwrap()
+            .chunk_size(1024)
+            .collect(),
+    );
+    assert!(chunks.len() > 1);
+
+    let mut res = Vec::new();
+    for chunk in chunks {
+        let chunk = chunk.unwrap();
+        assert!(chunk.len() < 1024 + 64);
+        res.extend_from_slice(&chunk);
+    }
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_stream_tiny_chunks() {
+    let mut expected: Vec<u8> = Vec::new();
+    document(10).generate(&mut expected).unwrap();
+
+    for chunk_size in [0, 1] {
+        let stream = document(10).into_stream().unwrap().chunk_size(chunk_size);
+
+        let mut res = Vec::new();
+        for chunk in block_on(stream.collect::<Vec<_>>()) {
+            res.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(res, expected, "Both values does not match...");
+    }
+}
+
+#[test]
+fn test_stream_lazy_children() {
+    let mut expected: Vec<u8> = Vec::new();
+    document(100).generate(&mut expected).unwrap();
+
+    let xml = document(10);
+    let stream = xml.into_stream_with_children((10..100).map(row)).unwrap();
+
+    let mut res = Vec::new();
+    for chunk in block_on(stream.collect::<Vec<_>>()) {
+        res.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Empty elements stay self-closing when no child is pulled
+    let mut xml = XMLBuilder::new().build();
+    xml.set_root_element(XMLElement::new("rows"));
+    let stream = xml.into_stream_with_children(std::iter::empty()).unwrap();
+    let res = block_on(stream.collect::<Vec<_>>()).remove(0).unwrap();
+    assert_eq!(
+        res, "<?xml version=\"1.0\"?>\n<rows />\n",
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_stream_children_without_root() {
+    let res = XMLBuilder::new()
+        .build()
+        .into_stream_with_children(std::iter::empty());
+
+    assert!(matches!(res, Err(XMLError::InsertError(_))));
+}
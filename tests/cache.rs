@@ -0,0 +1,105 @@
+use xml_builder::{RenderOptions, XMLBuilder, XMLElement};
+
+fn inventory(cached: bool) -> XMLElement {
+    let mut inventory = XMLElement::new("inventory");
+    for i in 1..=3 {
+        let mut item = XMLElement::new("item");
+        item.add_attribute("id", &i.to_string());
+        item.add_attribute("category", "tools");
+        for name in ["name", "stock"] {
+            let mut field = XMLElement::new(name);
+            field.add_text(format!("{name} {i}")).unwrap();
+            if cached {
+                field.enable_render_cache();
+            }
+            item.add_child(field).unwrap();
+        }
+        if cached {
+            item.enable_render_cache();
+        }
+        inventory.add_child(item).unwrap();
+    }
+    if cached {
+        inventory.enable_render_cache();
+    }
+
+    inventory
+}
+
+fn generate(root: &XMLElement) -> String {
+    let mut xml = XMLBuilder::new().sort_attributes(true).build();
+    xml.set_root_element(root.clone());
+
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    String::from_utf8(writer).unwrap()
+}
+
+fn update(root: &mut XMLElement, stock: &str) {
+    root.children_mut()[1].children_mut()[1].set_text(stock.into());
+    root.children_mut()[2].add_attribute("discount", "10%");
+}
+
+#[test]
+fn test_render_cache_identical_output() {
+    let mut cached = inventory(true);
+    let mut uncached = inventory(false);
+
+    for _ in 0..2 {
+        assert_eq!(
+            generate(&cached),
+            generate(&uncached),
+            "Both values does not match..."
+        );
+    }
+
+    update(&mut cached, "none");
+    update(&mut uncached, "none");
+    assert_eq!(
+        generate(&cached),
+        generate(&uncached),
+        "Both values does not match..."
+    );
+    assert!(generate(&cached).contains("<stock>none</stock>"));
+}
+
+#[test]
+fn test_render_cache_formatting() {
+    let cached = inventory(true);
+    let uncached = inventory(false);
+
+    for options in [
+        RenderOptions::pretty(),
+        RenderOptions::canonical(),
+        RenderOptions::pretty(),
+    ] {
+        let mut res: Vec<u8> = Vec::new();
+        cached.render(&mut res, &options).unwrap();
+        let mut expected: Vec<u8> = Vec::new();
+        uncached.render(&mut expected, &options).unwrap();
+
+        assert_eq!(res, expected, "Both values does not match...");
+    }
+}
+
+#[test]
+fn test_render_cache_lazy_children() {
+    let mut report = XMLElement::new("report");
+    report.enable_render_cache();
+    report
+        .set_lazy_children((0..2).map(|_| XMLElement::new("row")))
+        .unwrap();
+
+    let mut res: Vec<u8> = Vec::new();
+    report.render(&mut res, &RenderOptions::compact()).unwrap();
+    assert_eq!(
+        res, b"<report><row /><row /></report>",
+        "Both values does not match..."
+    );
+
+    // The consumed source is not replayed from the cache
+    let mut res: Vec<u8> = Vec::new();
+    report.render(&mut res, &RenderOptions::compact()).unwrap();
+    assert_eq!(res, b"<report />", "Both values does not match...");
+}
@@ -0,0 +1,32 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use xml_builder::{RenderOptions, XMLElement};
+
+/// Builds a house with attributes, a text child and an empty child.
+pub fn house() -> XMLElement {
+    let mut house = XMLElement::new("house");
+    house.add_attribute("rooms", "2");
+    house.add_attribute("owner", "Zoë");
+
+    house.add_child(text("room", "Kitchen")).unwrap();
+    house.add_child(XMLElement::new("garden")).unwrap();
+
+    house
+}
+
+/// Builds an element holding the given text.
+pub fn text(name: &str, text: &str) -> XMLElement {
+    let mut element = XMLElement::new(name);
+    element.add_text(text.into()).unwrap();
+
+    element
+}
+
+/// Renders the given element with the given options into a String.
+pub fn render(element: &XMLElement, options: &RenderOptions) -> String {
+    let mut writer: Vec<u8> = Vec::new();
+    element.render(&mut writer, options).unwrap();
+
+    String::from_utf8(writer).unwrap()
+}
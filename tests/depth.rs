@@ -0,0 +1,64 @@
+use xml_builder::{XMLBuilder, XMLElement, XMLError};
+
+const DEPTH: usize = 100_000;
+
+fn deep_tree(depth: usize) -> XMLElement {
+    let mut element = XMLElement::new("leaf");
+    for _ in 1..depth {
+        let mut parent = XMLElement::new("node");
+        parent.add_child(element).unwrap();
+        element = parent;
+    }
+
+    element
+}
+
+#[test]
+fn test_deep_tree() {
+    let mut xml = XMLBuilder::new().indent(false).break_lines(false).build();
+    xml.set_root_element(deep_tree(DEPTH));
+
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    let expected = format!(
+        "<?xml version=\"1.0\"?>{}<leaf />{}",
+        "<node>".repeat(DEPTH - 1),
+        "</node>".repeat(DEPTH - 1)
+    );
+    assert_eq!(
+        String::from_utf8(writer).unwrap(),
+        expected,
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_max_depth() {
+    let mut xml = XMLBuilder::new().max_depth(1000).build();
+    xml.set_root_element(deep_tree(1000));
+    assert!(xml.generate(Vec::new()).is_ok());
+
+    let mut xml = XMLBuilder::new().max_depth(1000).build();
+    xml.set_root_element(deep_tree(DEPTH));
+    assert!(matches!(
+        xml.generate(Vec::new()),
+        Err(XMLError::DepthLimitExceeded(1000))
+    ));
+}
+
+#[test]
+fn test_writer_max_depth() {
+    let mut writer = XMLBuilder::new().max_depth(2).build_writer(Vec::new());
+    writer.start_element("root").unwrap();
+    assert!(matches!(
+        writer.write_element(&deep_tree(2)),
+        Err(XMLError::DepthLimitExceeded(2))
+    ));
+
+    writer.start_element("child").unwrap();
+    assert!(matches!(
+        writer.start_element("grandchild"),
+        Err(XMLError::DepthLimitExceeded(2))
+    ));
+}
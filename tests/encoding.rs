@@ -0,0 +1,162 @@
+use std::io::BufReader;
+
+use xml_builder::{XML, XMLBuilder, XMLElement, XMLEncoding, XMLEvent, XMLReader};
+
+fn utf16le(input: &str) -> Vec<u8> {
+    input.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn utf16be(input: &str) -> Vec<u8> {
+    input.encode_utf16().flat_map(u16::to_be_bytes).collect()
+}
+
+fn generate(xml: XML) -> Vec<u8> {
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    writer
+}
+
+#[test]
+fn test_utf16le_with_bom() {
+    let input = utf16le(
+        "\u{feff}<?xml version=\"1.0\" encoding=\"UTF-16\"?>\n<house>Caf\u{e9} \u{1F3E0}</house>",
+    );
+
+    // A tiny buffer splits characters and surrogate pairs across reads
+    let xml = XML::parse(BufReader::with_capacity(3, input.as_slice())).unwrap();
+    assert_eq!(xml.output_encoding(), XMLEncoding::UTF16LE);
+    assert_eq!(
+        xml.root_element().unwrap().text(),
+        Some("Caf\u{e9} \u{1F3E0}")
+    );
+
+    let expected = utf16le(
+        "\u{feff}<?xml version=\"1.0\" encoding=\"UTF-16\"?>\n<house>Caf\u{e9} \u{1F3E0}</house>\n",
+    );
+    assert_eq!(generate(xml), expected, "Both values does not match...");
+}
+
+#[test]
+fn test_utf16be_without_bom() {
+    let input = utf16be("<?xml version=\"1.0\"?><house />");
+
+    let mut reader = XMLReader::new(input.as_slice());
+    assert!(matches!(
+        reader.next_event().unwrap(),
+        Some(XMLEvent::Declaration { .. })
+    ));
+    assert_eq!(reader.encoding(), Some(XMLEncoding::UTF16BE));
+}
+
+#[test]
+fn test_single_byte_encodings() {
+    let mut input = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><p>caf".to_vec();
+    input.extend_from_slice(b"\xE9</p>");
+
+    let xml = XML::parse(input.as_slice()).unwrap();
+    assert_eq!(xml.output_encoding(), XMLEncoding::ISO8859_1);
+    assert_eq!(xml.root_element().unwrap().text(), Some("caf\u{e9}"));
+
+    let mut input = b"<?xml version='1.0' encoding='windows-1252'?><p>".to_vec();
+    input.extend_from_slice(b"\x80 \x93quoted\x94</p>");
+
+    let xml = XML::parse(input.as_slice()).unwrap();
+    assert_eq!(xml.output_encoding(), XMLEncoding::Windows1252);
+    assert_eq!(
+        xml.root_element().unwrap().text(),
+        Some("\u{20AC} \u{201C}quoted\u{201D}")
+    );
+}
+
+#[test]
+fn test_lossless_round_trip_encodings() {
+    let mut input = b"<?xml version='1.0' encoding='windows-1252'?>\r\n<p a='\x80'>".to_vec();
+    input.extend_from_slice(b"\xE9\x81\xFF</p>\r\n");
+    let xml = XML::parse_lossless(input.as_slice()).unwrap();
+    assert_eq!(generate(xml), input, "Both values does not match...");
+
+    let input = utf16be("<?xml version=\"1.0\" encoding=\"UTF-16\"?>\n<p>\u{e9}</p>");
+    let xml = XML::parse_lossless(input.as_slice()).unwrap();
+    assert_eq!(generate(xml), input, "Both values does not match...");
+}
+
+#[test]
+fn test_output_encoding() {
+    let mut xml = XMLBuilder::new()
+        .output_encoding(XMLEncoding::ISO8859_1)
+        .build();
+
+    let mut root = XMLElement::new("price");
+    root.add_text("12 \u{20AC}".into()).unwrap();
+    xml.set_root_element(root);
+
+    let expected =
+        b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<price>12 &#8364;</price>\n".to_vec();
+    assert_eq!(generate(xml), expected, "Both values does not match...");
+}
+
+#[test]
+fn test_ascii_encoding() {
+    let input = "<?xml version=\"1.0\" encoding=\"US-ASCII\"?><a b=\"&#233;\">&#233;</a>";
+
+    let xml = XML::parse(input.as_bytes()).unwrap();
+    assert_eq!(xml.output_encoding(), XMLEncoding::ASCII);
+    assert_eq!(xml.root_element().unwrap().text(), Some("\u{e9}"));
+
+    let expected = b"<?xml version=\"1.0\" encoding=\"US-ASCII\"?>\n<a b=\"&#233;\">&#233;</a>\n";
+    assert_eq!(
+        generate(xml),
+        expected.to_vec(),
+        "Both values does not match..."
+    );
+
+    let xml = XML::parse_lossless(input.as_bytes()).unwrap();
+    assert_eq!(
+        generate(xml),
+        input.as_bytes(),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_unencodable_markup() {
+    let builder = || XMLBuilder::new().output_encoding(XMLEncoding::Windows1252);
+
+    // References are not expanded in names, which cannot be written
+    let mut xml = builder().build();
+    xml.set_root_element(XMLElement::new("\u{4E2D}"));
+    assert!(xml.generate(Vec::new()).is_err());
+
+    let mut root = XMLElement::new("a");
+    root.add_attribute("\u{4E2D}", "\u{4E2D}");
+    let mut xml = builder().build();
+    xml.set_root_element(root);
+    assert!(xml.generate(Vec::new()).is_err());
+
+    // Nor in CDATA sections, whose content would be changed
+    let mut writer = builder().build_writer(Vec::new());
+    writer.start_element("a").unwrap();
+    assert!(writer.cdata("\u{4E2D}").is_err());
+
+    // Text and attribute values use character references
+    let mut writer = builder().build_writer(Vec::new());
+    writer.start_element("a").unwrap();
+    writer.attribute("b", "\u{4E2D}").unwrap();
+    writer.cdata("x").unwrap();
+    writer.text("\u{4E2D}").unwrap();
+    writer.end_element("a").unwrap();
+    let expected = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?>\n<a b=\"&#20013;\"><![CDATA[x]]>&#20013;</a>\n";
+    assert_eq!(
+        writer.finish().unwrap(),
+        expected.to_vec(),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_unsupported_encoding() {
+    let input = "<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><a/>";
+
+    assert!(XML::parse(input.as_bytes()).is_err());
+}
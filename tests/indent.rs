@@ -0,0 +1,115 @@
+mod common;
+
+use common::{render, text};
+use xml_builder::{RenderOptions, XMLArena, XMLBuilder, XMLElement, XMLLineEnding};
+
+fn nested_house() -> XMLElement {
+    let mut house = XMLElement::new("house");
+    let mut floor = XMLElement::new("floor");
+    floor.add_child(text("room", "Kitchen")).unwrap();
+    house.add_child(floor).unwrap();
+
+    house
+}
+
+#[test]
+fn test_indent_units() {
+    let house = nested_house();
+
+    let expected = "<house>\n  <floor>\n    <room>Kitchen</room>\n  </floor>\n</house>\n";
+    let res = render(&house, &RenderOptions::pretty().indent_spaces(2));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected = "<house>\n\t\t<floor>\n\t\t\t\t<room>Kitchen</room>\n\t\t</floor>\n</house>\n";
+    let res = render(&house, &RenderOptions::pretty().indent_tabs(2));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected = "<house>\n. <floor>\n. . <room>Kitchen</room>\n. </floor>\n</house>\n";
+    let res = render(&house, &RenderOptions::pretty().indent_string(". "));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Units longer than the static buffers are allocated
+    let unit = " ".repeat(40);
+    let expected = format!(
+        "<house>\n{unit}<floor>\n{unit}{unit}<room>Kitchen</room>\n{unit}</floor>\n</house>\n"
+    );
+    let res = render(&house, &RenderOptions::pretty().indent_spaces(40));
+    assert_eq!(res, expected, "Both values does not match...");
+    assert_eq!(
+        RenderOptions::pretty().indent_spaces(40),
+        RenderOptions::pretty().indent_string(unit),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_line_endings() {
+    let house = nested_house();
+
+    for (line_ending, separator) in [
+        (XMLLineEnding::LF, "\n"),
+        (XMLLineEnding::CRLF, "\r\n"),
+        (XMLLineEnding::CR, "\r"),
+    ] {
+        let options = RenderOptions::pretty().line_ending(line_ending);
+        let expected = [
+            "<house>",
+            "\t<floor>",
+            "\t\t<room>Kitchen</room>",
+            "\t</floor>",
+            "</house>",
+            "",
+        ]
+        .join(separator);
+        let res = render(&house, &options);
+        assert_eq!(res, expected, "Both values does not match...");
+    }
+}
+
+#[test]
+fn test_builder_indent_and_line_ending() {
+    let builder = || {
+        XMLBuilder::new()
+            .indent_spaces(2)
+            .line_ending(XMLLineEnding::CRLF)
+    };
+    let expected = "<?xml version=\"1.0\"?>\r\n<house>\r\n  <floor>\r\n    <room>Kitchen</room>\r\n  </floor>\r\n</house>\r\n";
+
+    let mut xml = builder().build();
+    xml.set_root_element(nested_house());
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+    let res = String::from_utf8(writer).unwrap();
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let mut writer = builder().build_writer(Vec::new());
+    writer.start_element("house").unwrap();
+    writer.start_element("floor").unwrap();
+    writer.start_element("room").unwrap();
+    writer.text("Kitchen").unwrap();
+    writer.end_element("room").unwrap();
+    writer.end_element("floor").unwrap();
+    writer.end_element("house").unwrap();
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_arena_indent_and_line_ending() {
+    let house = nested_house();
+    let arena = XMLArena::from(&house);
+    let options = RenderOptions::pretty()
+        .indent_string("  ")
+        .line_ending(XMLLineEnding::CRLF);
+
+    let mut writer: Vec<u8> = Vec::new();
+    arena
+        .render(arena.root().unwrap(), &mut writer, &options)
+        .unwrap();
+    let res = String::from_utf8(writer).unwrap();
+    assert_eq!(
+        res,
+        render(&house, &options),
+        "Both values does not match..."
+    );
+}
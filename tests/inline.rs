@@ -0,0 +1,138 @@
+mod common;
+
+use common::{render, text};
+use xml_builder::{RenderOptions, XMLArena, XMLBuilder, XMLElement};
+
+fn library() -> XMLElement {
+    let mut library = XMLElement::new("library");
+
+    let mut book = XMLElement::new("book");
+    book.add_attribute("id", "1");
+    book.add_child(text("title", "Dune")).unwrap();
+    library.add_child(book).unwrap();
+
+    let mut book = XMLElement::new("book");
+    book.add_attribute("id", "2");
+    book.add_child(text("title", "The Left Hand of Darkness"))
+        .unwrap();
+    book.add_child(text("author", "Ursula K. Le Guin")).unwrap();
+    library.add_child(book).unwrap();
+    library.add_child(XMLElement::new("shelf")).unwrap();
+
+    library
+}
+
+#[test]
+fn test_inline_short_elements() {
+    let library = library();
+
+    // The first book takes exactly 40 characters, its indentation included
+    let expected = "<library>\n\t<book id=\"1\"><title>Dune</title></book>\n\t<book id=\"2\">\n\t\t<title>The Left Hand of Darkness</title>\n\t\t<author>Ursula K. Le Guin</author>\n\t</book>\n\t<shelf />\n</library>\n";
+    let res = render(&library, &RenderOptions::pretty().inline_width(40));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected = render(&library, &RenderOptions::pretty());
+    let res = render(&library, &RenderOptions::pretty().inline_width(39));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let res = render(
+        &library,
+        &RenderOptions::pretty().indent_spaces(4).inline_width(40),
+    );
+    let expected = render(&library, &RenderOptions::pretty().indent_spaces(4));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected = render(&library, &RenderOptions::compact()) + "\n";
+    let res = render(&library, &RenderOptions::pretty().inline_width(200));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_inline_text_elements() {
+    // Text is never wrapped, even when wider than the width
+    let mut book = XMLElement::new("book");
+    book.add_child(text("title", "The Left Hand of Darkness"))
+        .unwrap();
+    let expected = "<book>\n\t<title>The Left Hand of Darkness</title>\n</book>\n";
+    let res = render(&book, &RenderOptions::pretty().inline_width(10));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Elements holding multi-line text do not fit on a single line
+    let mut poem = XMLElement::new("poem");
+    poem.add_child(text("verse", "Roses\nViolets")).unwrap();
+    let expected = "<poem>\n\t<verse>Roses\nViolets</verse>\n</poem>\n";
+    let res = render(&poem, &RenderOptions::pretty().inline_width(80));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_inline_overrides() {
+    let mut library = library();
+    library.children_mut()[0].enable_line_breaks();
+
+    let expected = "<library>\n\t<book id=\"1\">\n\t\t<title>Dune</title>\n\t</book>\n";
+    let res = render(&library, &RenderOptions::pretty().inline_width(40));
+    assert!(res.starts_with(expected), "Both values does not match...");
+}
+
+#[test]
+fn test_inline_document() {
+    let mut xml = XMLBuilder::new().inline_width(40).build();
+    xml.set_root_element(library());
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    let res = String::from_utf8(writer).unwrap();
+    let expected = format!(
+        "<?xml version=\"1.0\"?>\n{}",
+        render(&library(), &RenderOptions::pretty().inline_width(40))
+    );
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_inline_streaming() {
+    let mut a = XMLElement::new("a");
+    a.add_child(XMLElement::new("b")).unwrap();
+
+    // Streamed elements cannot be measured, unlike the ones written whole
+    let mut writer = XMLBuilder::new().inline_width(80).build_writer(Vec::new());
+    writer.start_element("root").unwrap();
+    writer.write_element(&a).unwrap();
+    writer.end_element("root").unwrap();
+
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let expected = "<?xml version=\"1.0\"?>\n<root>\n\t<a><b /></a>\n</root>\n";
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_inline_consistency() {
+    let library = library();
+    let arena = XMLArena::from(&library);
+    let mut cached = library.clone();
+    cached.enable_render_cache();
+    for book in cached.children_mut() {
+        book.enable_render_cache();
+    }
+
+    for width in [10, 39, 40, 80, 200] {
+        let options = RenderOptions::pretty().inline_width(width);
+        let expected = render(&library, &options);
+
+        let mut writer: Vec<u8> = Vec::new();
+        arena
+            .render(arena.root().unwrap(), &mut writer, &options)
+            .unwrap();
+        let res = String::from_utf8(writer).unwrap();
+        assert_eq!(res, expected, "Both values does not match...");
+
+        for _ in 0..2 {
+            let res = render(&cached, &options);
+            assert_eq!(res, expected, "Both values does not match...");
+        }
+
+        let len = library.rendered_len(&options).unwrap();
+        assert_eq!(len, expected.len(), "Both values does not match...");
+    }
+}
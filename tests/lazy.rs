@@ -0,0 +1,87 @@
+use xml_builder::{RenderOptions, XML, XMLBuilder, XMLElement, XMLError};
+
+fn row(index: usize) -> XMLElement {
+    let mut row = XMLElement::new("row");
+    row.add_attribute("id", &index.to_string());
+
+    row
+}
+
+fn document(rows: XMLElement) -> XML {
+    let mut xml = XMLBuilder::new().build();
+
+    let mut report = XMLElement::new("report");
+    report.add_child(XMLElement::new("header")).unwrap();
+    report.add_child(rows).unwrap();
+    report.add_child(XMLElement::new("footer")).unwrap();
+    xml.set_root_element(report);
+
+    xml
+}
+
+fn generate(xml: XML) -> String {
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    String::from_utf8(writer).unwrap()
+}
+
+#[test]
+fn test_lazy_children() {
+    let mut rows = XMLElement::new("rows");
+    for index in 0..5 {
+        rows.add_child(row(index)).unwrap();
+    }
+    let expected = generate(document(rows));
+
+    let mut rows = XMLElement::new("rows");
+    rows.add_child(row(0)).unwrap();
+    rows.set_lazy_children((1..5).map(row)).unwrap();
+    assert_eq!(rows.children().len(), 1);
+
+    let res = generate(document(rows));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_lazy_children_closure() {
+    let mut rows = XMLElement::new("rows");
+    let mut cursor = 0..3;
+    rows.set_lazy_children(std::iter::from_fn(move || cursor.next().map(row)))
+        .unwrap();
+
+    let mut res: Vec<u8> = Vec::new();
+    rows.render(&mut res, &RenderOptions::pretty()).unwrap();
+    let expected = "<rows>\n\t<row id=\"0\" />\n\t<row id=\"1\" />\n\t<row id=\"2\" />\n</rows>\n";
+    assert_eq!(
+        String::from_utf8(res).unwrap(),
+        expected,
+        "Both values does not match..."
+    );
+
+    // The source is consumed by the first rendering
+    let mut res: Vec<u8> = Vec::new();
+    rows.render(&mut res, &RenderOptions::pretty()).unwrap();
+    assert_eq!(
+        String::from_utf8(res).unwrap(),
+        "<rows />\n",
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_lazy_children_and_text() {
+    let mut rows = XMLElement::new("rows");
+    rows.set_lazy_children(std::iter::empty()).unwrap();
+    assert!(matches!(
+        rows.add_text("text".into()),
+        Err(XMLError::InsertError(_))
+    ));
+
+    let mut text = XMLElement::new("text");
+    text.add_text("text".into()).unwrap();
+    assert!(matches!(
+        text.set_lazy_children(std::iter::empty()),
+        Err(XMLError::InsertError(_))
+    ));
+}
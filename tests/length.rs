@@ -0,0 +1,78 @@
+use xml_builder::{RenderOptions, XML, XMLBuilder, XMLElement, XMLEncoding, XMLError};
+
+fn unicode_house() -> XMLElement {
+    let mut house = XMLElement::new("house");
+    house.add_attribute("owner", "Zoë & Chloé");
+
+    for i in 1..=3 {
+        let mut room = XMLElement::new("room");
+        room.add_attribute("number", &i.to_string());
+        room.add_text(format!("Room n°{i} – 20 m²")).unwrap();
+        house.add_child(room).unwrap();
+    }
+    house.add_child(XMLElement::new("garden")).unwrap();
+
+    house
+}
+
+fn generate(xml: XML) -> Vec<u8> {
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    writer
+}
+
+#[test]
+fn test_document_rendered_len() {
+    for encoding in [
+        XMLEncoding::UTF8,
+        XMLEncoding::UTF16LE,
+        XMLEncoding::ISO8859_1,
+        XMLEncoding::ASCII,
+    ] {
+        let mut xml = XMLBuilder::new()
+            .standalone(Some(true))
+            .output_encoding(encoding)
+            .build();
+        xml.set_root_element(unicode_house());
+
+        let len = xml.rendered_len().unwrap();
+        assert_eq!(len, generate(xml).len(), "Both values does not match...");
+    }
+
+    let input = "<?xml version=\"1.0\"?>\n<!-- config -->\n<config>\n  <user name=\"caf&#233;\"/>\n</config>\n<!-- end -->\n";
+    let xml = XML::parse_lossless(input.as_bytes()).unwrap();
+    let len = xml.rendered_len().unwrap();
+    assert_eq!(len, generate(xml).len(), "Both values does not match...");
+}
+
+#[test]
+fn test_element_rendered_len() {
+    let house = unicode_house();
+
+    for options in [RenderOptions::pretty(), RenderOptions::canonical()] {
+        let mut writer: Vec<u8> = Vec::new();
+        house.render(&mut writer, &options).unwrap();
+
+        let len = house.rendered_len(&options).unwrap();
+        assert_eq!(len, writer.len(), "Both values does not match...");
+    }
+}
+
+#[test]
+fn test_rendered_len_lazy_children() {
+    let mut rows = XMLElement::new("rows");
+    rows.set_lazy_children((0..2).map(|_| XMLElement::new("row")))
+        .unwrap();
+
+    let res = rows.rendered_len(&RenderOptions::compact());
+    assert!(matches!(res, Err(XMLError::MeasureError(_))));
+
+    // Lazy children are left to the rendering
+    let mut writer: Vec<u8> = Vec::new();
+    rows.render(&mut writer, &RenderOptions::compact()).unwrap();
+    assert_eq!(
+        writer, b"<rows><row /><row /></rows>",
+        "Both values does not match..."
+    );
+}
@@ -0,0 +1,176 @@
+use std::io::BufReader;
+
+use xml_builder::{XML, XMLError, XMLLimits, XMLReader};
+
+const BILLION_LAUGHS: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE lolz [
+ <!ENTITY lol "lol">
+ <!ENTITY lol1 "&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;&lol;">
+ <!ENTITY lol2 "&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;&lol1;">
+ <!ENTITY lol3 "&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;&lol2;">
+ <!ENTITY lol4 "&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;&lol3;">
+ <!ENTITY lol5 "&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;&lol4;">
+ <!ENTITY lol6 "&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;&lol5;">
+ <!ENTITY lol7 "&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;&lol6;">
+ <!ENTITY lol8 "&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;&lol7;">
+ <!ENTITY lol9 "&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;&lol8;">
+]>
+<lolz>&lol9;</lolz>"#;
+
+fn parse_error(input: &str, limits: XMLLimits) -> XMLError {
+    match XML::parse_with_limits(input.as_bytes(), limits) {
+        Ok(_) => panic!("{input} should not be parsed"),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn test_internal_entities() {
+    let input = r#"<!DOCTYPE house [<!ENTITY owner "Tom &amp; Jerry">]>
+<house owner="&owner;">&owner;</house>"#;
+
+    let xml = XML::parse_untrusted(input.as_bytes()).unwrap();
+    let root = xml.root_element().unwrap();
+
+    assert_eq!(root.attributes()[0].1, "Tom &amp; Jerry");
+    assert_eq!(root.text(), Some("Tom &amp; Jerry"));
+}
+
+#[test]
+fn test_billion_laughs() {
+    let limits = XMLLimits::new().max_entity_depth(usize::MAX);
+    assert!(matches!(
+        parse_error(BILLION_LAUGHS, limits),
+        XMLError::EntityExpansionExceeded(_)
+    ));
+
+    assert!(matches!(
+        XML::parse_untrusted(BILLION_LAUGHS.as_bytes()),
+        Err(XMLError::EntityDepthExceeded(4))
+    ));
+}
+
+#[test]
+fn test_entity_chain() {
+    // Long chains of entities are expanded without overflowing the stack
+    let chain = |length: usize| {
+        let mut input = String::from("<!DOCTYPE a [<!ENTITY e0 \"end\">");
+        for index in 1..length {
+            input.push_str(&format!("<!ENTITY e{index} \"&e{};\">", index - 1));
+        }
+        input.push_str(&format!("]><a>&e{};</a>", length - 1));
+        input
+    };
+    assert!(matches!(
+        XML::parse(chain(100_000).as_bytes()),
+        Err(XMLError::EntityDepthExceeded(64))
+    ));
+
+    let xml = XML::parse_with_limits(chain(100_000).as_bytes(), XMLLimits::unlimited()).unwrap();
+    assert_eq!(xml.root_element().unwrap().text(), Some("end"));
+
+    let xml = XML::parse(chain(64).as_bytes()).unwrap();
+    assert_eq!(xml.root_element().unwrap().text(), Some("end"));
+}
+
+#[test]
+fn test_recursive_entity() {
+    let input = r#"<!DOCTYPE a [<!ENTITY a "&b;"><!ENTITY b "&a;">]><a>&a;</a>"#;
+
+    assert!(matches!(
+        parse_error(input, XMLLimits::unlimited()),
+        XMLError::ParseError(_)
+    ));
+}
+
+#[test]
+fn test_external_entity() {
+    let input = r#"<!DOCTYPE a [<!ENTITY xxe SYSTEM "file:///etc/passwd">]><a>&xxe;</a>"#;
+
+    assert!(matches!(
+        parse_error(input, XMLLimits::unlimited()),
+        XMLError::ExternalEntity(name) if name == "xxe"
+    ));
+}
+
+#[test]
+fn test_max_depth() {
+    let input = "<a><b><c /></b></a>";
+
+    assert!(XML::parse_with_limits(input.as_bytes(), XMLLimits::new().max_depth(3)).is_ok());
+    assert!(matches!(
+        parse_error(input, XMLLimits::new().max_depth(2)),
+        XMLError::DepthLimitExceeded(2)
+    ));
+}
+
+#[test]
+fn test_max_attributes() {
+    let input = r#"<a b="1" c="2" d="3" />"#;
+
+    assert!(matches!(
+        parse_error(input, XMLLimits::new().max_attributes(2)),
+        XMLError::TooManyAttributes(2)
+    ));
+}
+
+#[test]
+fn test_max_name_length() {
+    let input = "<element />";
+
+    assert!(matches!(
+        parse_error(input, XMLLimits::new().max_name_length(4)),
+        XMLError::NameTooLong(4)
+    ));
+}
+
+#[test]
+fn test_max_text_length() {
+    let input = "<a>This text is too long</a>";
+    assert!(matches!(
+        parse_error(input, XMLLimits::new().max_text_length(8)),
+        XMLError::TextTooLong(8)
+    ));
+
+    let input = r#"<!DOCTYPE a [<!ENTITY e "too long">]><a>&e;&e;</a>"#;
+    assert!(matches!(
+        parse_error(input, XMLLimits::new().max_text_length(12)),
+        XMLError::TextTooLong(12)
+    ));
+}
+
+#[test]
+fn test_limits_while_reading_tags() {
+    // Oversized attribute values are rejected before being read whole
+    let input = format!("<a b=\"{}\" />", "x".repeat(5_000_000));
+    let source = BufReader::with_capacity(1024, input.as_bytes());
+    let mut reader = XMLReader::with_limits(source, XMLLimits::new().max_text_length(16));
+    assert!(matches!(
+        reader.next_event(),
+        Err(XMLError::TextTooLong(16))
+    ));
+    assert!(reader.into_inner().into_inner().len() > 4_900_000);
+
+    let input = format!("<a {}=\"b\" />", "x".repeat(64));
+    assert!(matches!(
+        parse_error(&input, XMLLimits::new().max_name_length(16)),
+        XMLError::NameTooLong(16)
+    ));
+
+    let input = format!("<a{}b=\"c\" />", " ".repeat(64));
+    assert!(matches!(
+        parse_error(&input, XMLLimits::new().max_text_length(16)),
+        XMLError::TextTooLong(16)
+    ));
+}
+
+#[test]
+fn test_max_text_length_across_events() {
+    let input = "<a>abcd<!-- split -->efgh<![CDATA[ijkl]]></a>";
+
+    assert!(matches!(
+        parse_error(input, XMLLimits::new().max_text_length(8)),
+        XMLError::TextTooLong(8)
+    ));
+    assert!(XML::parse_with_limits(input.as_bytes(), XMLLimits::new().max_text_length(12)).is_ok());
+}
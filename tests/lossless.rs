@@ -0,0 +1,96 @@
+use xml_builder::{XML, XMLElement};
+
+const CONFIG: &str = "\u{feff}<?xml version='1.0'   encoding=\"UTF-8\" ?>
+<!-- Server configuration -->
+<!DOCTYPE config [<!ENTITY host \"localhost\">]>
+<config   version = '2' >\r
+  <server host=\"&host;\" port='8080'/>\r
+  <!-- Users allowed to connect -->\r
+  <users>
+      <user name=\"caf&#233;\" >Tom &amp; Jerry</user >
+      <user name=\"bob\"><![CDATA[<admin>]]></user>
+  </users>
+  <motd>Welcome to <b>our</b> server</motd>
+  <empty></empty>
+</config>
+<?post-processing done?>
+";
+
+fn generate(xml: XML) -> String {
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    String::from_utf8(writer).unwrap()
+}
+
+#[test]
+fn test_lossless_round_trip() {
+    let xml = XML::parse_lossless(CONFIG.as_bytes()).unwrap();
+
+    assert_eq!(generate(xml), CONFIG, "Both values does not match...");
+}
+
+#[test]
+fn test_lossless_content() {
+    let xml = XML::parse_lossless(CONFIG.as_bytes()).unwrap();
+
+    let root = xml.root_element().unwrap();
+    assert_eq!(root.name(), "config");
+    assert_eq!(root.children().len(), 4);
+
+    let user = &root.children()[1].children()[0];
+    assert_eq!(user.attributes()[0].1, "caf&#233;");
+    assert_eq!(user.text(), Some("Tom &amp; Jerry"));
+}
+
+#[test]
+fn test_lossless_minimal_diff() {
+    let mut xml = XML::parse_lossless(CONFIG.as_bytes()).unwrap();
+
+    let root = xml.root_element_mut().unwrap();
+    root.children_mut()[0].set_attribute("port", "9090");
+
+    let users = &mut root.children_mut()[1];
+    users.children_mut()[1].set_text("Alice".into());
+
+    let mut user = XMLElement::new("user");
+    user.add_attribute("name", "carol");
+    users.add_child(user).unwrap();
+
+    let mut empty = XMLElement::new("empty");
+    empty.add_attribute("added", "yes");
+    root.add_child(empty).unwrap();
+
+    let expected = CONFIG
+        .replace("port='8080'", "port='9090'")
+        .replace("<![CDATA[<admin>]]>", "Alice")
+        .replace(
+            "</user>\n  </users>",
+            "</user>\n      <user name=\"carol\" />\n  </users>",
+        )
+        .replace(
+            "<empty></empty>\n",
+            "<empty></empty>\n  <empty added=\"yes\" />\n",
+        );
+
+    assert_eq!(generate(xml), expected, "Both values does not match...");
+}
+
+#[test]
+fn test_lossless_whitespace_container() {
+    let input = "<config>\n  <servers>\n  </servers>\n</config>\n";
+    let mut xml = XML::parse_lossless(input.as_bytes()).unwrap();
+
+    let servers = &mut xml.root_element_mut().unwrap().children_mut()[0];
+    assert_eq!(servers.text(), None);
+    let mut server = XMLElement::new("server");
+    server.add_attribute("host", "localhost");
+    servers.add_child(server).unwrap();
+
+    let expected =
+        "<config>\n  <servers>\n  <server host=\"localhost\" />\n  </servers>\n</config>\n";
+    assert_eq!(generate(xml), expected, "Both values does not match...");
+
+    let xml = XML::parse_lossless(input.as_bytes()).unwrap();
+    assert_eq!(generate(xml), input, "Both values does not match...");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{house, render};
+use xml_builder::{RenderOptions, XMLBuilder};
+
+#[test]
+fn test_render_options_presets() {
+    let house = house();
+
+    let expected =
+        "<house rooms=\"2\" owner=\"Zoë\">\n\t<room>Kitchen</room>\n\t<garden />\n</house>\n";
+    let res = render(&house, &RenderOptions::pretty());
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected = "<house rooms=\"2\" owner=\"Zoë\"><room>Kitchen</room><garden /></house>";
+    let res = render(&house, &RenderOptions::compact());
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected = "<house owner=\"Zoë\" rooms=\"2\"><room>Kitchen</room><garden></garden></house>";
+    let res = render(&house, &RenderOptions::canonical());
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_render_options_values() {
+    assert_eq!(
+        RenderOptions::default(),
+        RenderOptions::pretty(),
+        "Both values does not match..."
+    );
+    assert_eq!(
+        RenderOptions::compact()
+            .sort_attributes(true)
+            .expand_empty_tags(true),
+        RenderOptions::canonical(),
+        "Both values does not match..."
+    );
+    assert_ne!(
+        RenderOptions::pretty(),
+        RenderOptions::pretty().max_depth(4)
+    );
+
+    let options = RenderOptions::canonical();
+    let shared = options.clone();
+    assert_eq!(
+        render(&house(), &options),
+        render(&house(), &shared),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_builder_render_options() {
+    let mut xml = XMLBuilder::new()
+        .render_options(RenderOptions::compact())
+        .build();
+    xml.set_root_element(house());
+    assert_eq!(
+        xml.render_options(),
+        &RenderOptions::compact(),
+        "Both values does not match..."
+    );
+
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+    let expected = "<?xml version=\"1.0\"?><house rooms=\"2\" owner=\"Zoë\"><room>Kitchen</room><garden /></house>";
+    let res = String::from_utf8(writer).unwrap();
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Individual setters adjust the options set beforehand
+    let xml = XMLBuilder::new()
+        .render_options(RenderOptions::compact())
+        .sort_attributes(true)
+        .build();
+    assert_eq!(
+        xml.render_options(),
+        &RenderOptions::compact().sort_attributes(true),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_set_render_options() {
+    let mut xml = XMLBuilder::new().build();
+    xml.set_root_element(house());
+    xml.set_render_options(RenderOptions::canonical());
+
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+    let expected = "<?xml version=\"1.0\"?><house owner=\"Zoë\" rooms=\"2\"><room>Kitchen</room><garden></garden></house>";
+    let res = String::from_utf8(writer).unwrap();
+    assert_eq!(res, expected, "Both values does not match...");
+}
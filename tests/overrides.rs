@@ -0,0 +1,126 @@
+mod common;
+
+use common::{render, text};
+use xml_builder::{RenderOptions, XMLBuilder, XMLElement};
+
+fn house_with_address() -> XMLElement {
+    let mut house = XMLElement::new("house");
+
+    let mut address = XMLElement::new("address");
+    address.disable_indentation();
+    address.disable_line_breaks();
+    address.add_child(text("street", "Main street")).unwrap();
+    address.add_child(text("city", "Paris")).unwrap();
+    house.add_child(address).unwrap();
+    house.add_child(XMLElement::new("room")).unwrap();
+
+    house
+}
+
+#[test]
+fn test_compact_subtree() {
+    let expected = "<house>\n\t<address><street>Main street</street><city>Paris</city></address>\n\t<room />\n</house>\n";
+    let res = render(&house_with_address(), &RenderOptions::pretty());
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let mut xml = XMLBuilder::new().build();
+    xml.set_root_element(house_with_address());
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+    let res = String::from_utf8(writer).unwrap();
+    assert_eq!(
+        res,
+        format!("<?xml version=\"1.0\"?>\n{expected}"),
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_single_line_subtree() {
+    let mut address = XMLElement::new("address");
+    address.disable_line_breaks();
+    address.add_child(text("street", "Main")).unwrap();
+    address.add_child(text("city", "X")).unwrap();
+    let mut house = XMLElement::new("house");
+    house.add_child(address).unwrap();
+
+    // Disabling line breaks alone keeps the content unindented
+    let expected = "<house>\n\t<address><street>Main</street><city>X</city></address>\n</house>\n";
+    let res = render(&house, &RenderOptions::pretty());
+    assert_eq!(res, expected, "Both values does not match...");
+
+    house.children_mut()[0].enable_indentation();
+    let expected =
+        "<house>\n\t<address>\t\t<street>Main</street>\t\t<city>X</city>\t</address>\n</house>\n";
+    let res = render(&house, &RenderOptions::pretty());
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_line_breaks_subtree() {
+    let mut list = XMLElement::new("list");
+    list.enable_line_breaks();
+    list.add_child(XMLElement::new("item")).unwrap();
+    list.add_child(XMLElement::new("item")).unwrap();
+
+    let mut page = XMLElement::new("page");
+    page.add_child(list).unwrap();
+    page.add_child(XMLElement::new("footer")).unwrap();
+
+    let expected = "<page><list>\n<item />\n<item />\n</list><footer /></page>";
+    let res = render(&page, &RenderOptions::compact());
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_expand_empty_tags_subtree() {
+    let mut script = XMLElement::new("script");
+    script.add_attribute("src", "main.js");
+    script.enable_empty_tags_expansion();
+
+    let mut head = XMLElement::new("head");
+    head.add_child(script).unwrap();
+    head.add_child(XMLElement::new("meta")).unwrap();
+
+    let expected = "<head>\n\t<script src=\"main.js\"></script>\n\t<meta />\n</head>\n";
+    let res = render(&head, &RenderOptions::pretty());
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_nearest_override() {
+    let mut inner = XMLElement::new("inner");
+    inner.enable_empty_tags_expansion();
+    inner.add_child(XMLElement::new("leaf")).unwrap();
+
+    let mut outer = XMLElement::new("outer");
+    outer.disable_empty_tags_expansion();
+    outer.add_child(XMLElement::new("leaf")).unwrap();
+    outer.add_child(inner).unwrap();
+
+    let mut root = XMLElement::new("root");
+    root.add_child(outer).unwrap();
+    root.add_child(XMLElement::new("leaf")).unwrap();
+
+    let expected = "<root><outer><leaf /><inner><leaf></leaf></inner></outer><leaf></leaf></root>";
+    let res = render(&root, &RenderOptions::canonical());
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_overrides_cached() {
+    let mut house = house_with_address();
+    house.children_mut()[0].enable_render_cache();
+    let expected = render(&house, &RenderOptions::pretty());
+    assert_eq!(
+        render(&house, &RenderOptions::pretty()),
+        expected,
+        "Both values does not match..."
+    );
+
+    // Overrides set after the element is cached are honored
+    house.children_mut()[0].enable_line_breaks();
+    let res = render(&house, &RenderOptions::pretty());
+    let expected = "<house>\n\t<address>\n<street>Main street</street>\n<city>Paris</city>\n</address>\n\t<room />\n</house>\n";
+    assert_eq!(res, expected, "Both values does not match...");
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "rayon")]
+
+use xml_builder::{XML, XMLBuilder, XMLElement, XMLError};
+
+fn export(builder: XMLBuilder) -> XML {
+    let mut xml = builder.sort_attributes(true).build();
+
+    let mut header = XMLElement::new("header");
+    header.add_attribute("vendor", "ACME");
+    header.enable_render_cache();
+
+    let mut export = XMLElement::new("export");
+    for i in 0..1_000 {
+        let mut record = XMLElement::new("record");
+        record.add_attribute("id", &i.to_string());
+        record.add_attribute("batch", &(i % 7).to_string());
+        record.add_child(header.clone()).unwrap();
+
+        let mut lines = XMLElement::new("lines");
+        lines
+            .set_lazy_children((0..i % 5).map(|line| {
+                let mut element = XMLElement::new("line");
+                element.add_text(format!("line {line}")).unwrap();
+                element
+            }))
+            .unwrap();
+        record.add_child(lines).unwrap();
+        export.add_child(record).unwrap();
+    }
+    xml.set_root_element(export);
+
+    xml
+}
+
+fn generate(xml: XML) -> Result<Vec<u8>, XMLError> {
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer)?;
+
+    Ok(writer)
+}
+
+#[test]
+fn test_parallel_rendering() {
+    let sequential = generate(export(XMLBuilder::new())).unwrap();
+    let parallel = generate(export(XMLBuilder::new().parallel_rendering(2))).unwrap();
+
+    assert_eq!(parallel, sequential, "Both values does not match...");
+}
+
+#[test]
+fn test_parallel_rendering_errors() {
+    let builder = XMLBuilder::new().parallel_rendering(2).max_depth(3);
+    let res = generate(export(builder));
+
+    assert!(matches!(res, Err(XMLError::DepthLimitExceeded(3))));
+}
@@ -0,0 +1,161 @@
+use std::io::BufReader;
+
+use xml_builder::{XML, XMLEvent, XMLReader};
+
+fn read_events(input: &str) -> Vec<XMLEvent> {
+    XMLReader::new(input.as_bytes())
+        .collect::<xml_builder::Result<Vec<_>>>()
+        .unwrap()
+}
+
+#[test]
+fn test_reader_events() {
+    let events = read_events(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<!DOCTYPE house>
+<!-- A house -->
+<house rooms=\"2\"><room number='1'>Tom &amp; Jerry&#33;</room><garden /><![CDATA[<raw>]]><?pi some data?></house>",
+    );
+
+    let expected = vec![
+        XMLEvent::Declaration {
+            version: "1.0".into(),
+            encoding: Some("UTF-8".into()),
+            standalone: None,
+        },
+        XMLEvent::DocumentType("house".into()),
+        XMLEvent::Comment(" A house ".into()),
+        XMLEvent::StartElement {
+            name: "house".into(),
+            attributes: vec![("rooms".into(), "2".into())],
+        },
+        XMLEvent::StartElement {
+            name: "room".into(),
+            attributes: vec![("number".into(), "1".into())],
+        },
+        XMLEvent::Text("Tom & Jerry!".into()),
+        XMLEvent::EndElement {
+            name: "room".into(),
+        },
+        XMLEvent::StartElement {
+            name: "garden".into(),
+            attributes: vec![],
+        },
+        XMLEvent::EndElement {
+            name: "garden".into(),
+        },
+        XMLEvent::CData("<raw>".into()),
+        XMLEvent::ProcessingInstruction {
+            target: "pi".into(),
+            data: Some("some data".into()),
+        },
+        XMLEvent::EndElement {
+            name: "house".into(),
+        },
+    ];
+
+    assert_eq!(events, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_reader_small_buffer() {
+    let input = "<root><element attribute=\"a > b\">text</element><!-- comment --></root>";
+
+    let expected = read_events(input);
+    let events = XMLReader::new(BufReader::with_capacity(1, input.as_bytes()))
+        .collect::<xml_builder::Result<Vec<_>>>()
+        .unwrap();
+
+    assert_eq!(events, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_reader_depth() {
+    let mut reader = XMLReader::new("<a><b/></a>".as_bytes());
+
+    let mut depths = Vec::new();
+    while reader.next_event().unwrap().is_some() {
+        depths.push(reader.depth());
+    }
+
+    assert_eq!(depths, vec![1, 2, 1, 0], "Both values does not match...");
+}
+
+#[test]
+fn test_reader_errors() {
+    for input in [
+        "<a></b>",
+        "<a>",
+        "<a/><b/>",
+        "text<a/>",
+        "<a b=\"1\" b=\"2\"/>",
+        "<a b=1/>",
+        "<a>&unknown;</a>",
+        "<a><?xml version=\"1.0\"?></a>",
+        "<a>&#0;</a>",
+        "<a>&#x1F;</a>",
+        "<a b=\"&#xFFFE;\"/>",
+        "<a>&#xFFFF;</a>",
+        "",
+    ] {
+        let result: xml_builder::Result<Vec<_>> = XMLReader::new(input.as_bytes()).collect();
+        assert!(result.is_err(), "{input} should not be parsed");
+    }
+}
+
+#[test]
+fn test_reader_doctype_subset() {
+    let events = read_events(
+        "<!DOCTYPE r [<!-- don't --><?pi \"?><!ENTITY e \"it's ]>\"><!-- <!ENTITY e \"x\"> -->]><r>&e;</r>",
+    );
+
+    let expected = vec![
+        XMLEvent::DocumentType(
+            "r [<!-- don't --><?pi \"?><!ENTITY e \"it's ]>\"><!-- <!ENTITY e \"x\"> -->]".into(),
+        ),
+        XMLEvent::StartElement {
+            name: "r".into(),
+            attributes: vec![],
+        },
+        XMLEvent::Text("it's ]>".into()),
+        XMLEvent::EndElement { name: "r".into() },
+    ];
+
+    assert_eq!(events, expected, "Both values does not match...");
+    assert!(XML::parse_untrusted("<a>&#0;</a>".as_bytes()).is_err());
+}
+
+#[test]
+fn test_parse() {
+    let input = "<?xml version=\"1.1\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<!-- Comments are discarded -->
+<house rooms=\"2\">
+    <room number=\"1\">Tom &amp; Jerry</room>
+    <garden/>
+</house>";
+
+    let xml = XML::parse(input.as_bytes()).unwrap();
+
+    let root = xml.root_element().unwrap();
+    assert_eq!(root.name(), "house");
+    assert_eq!(root.children().len(), 2);
+    assert_eq!(root.children()[0].text(), Some("Tom &amp; Jerry"));
+
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    let expected = "<?xml version=\"1.1\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<house rooms=\"2\">
+\t<room number=\"1\">Tom &amp; Jerry</room>
+\t<garden />
+</house>\n";
+    let res = std::str::from_utf8(&writer).unwrap();
+
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+#[should_panic(expected = "mixed text and elements content is not supported")]
+fn test_parse_mixed_content() {
+    XML::parse("<p>Some <b>bold</b> text</p>".as_bytes()).unwrap();
+}
@@ -0,0 +1,81 @@
+use xml_builder::{XMLBuilder, XMLElement};
+
+fn header() -> XMLElement {
+    let mut header = XMLElement::new("header");
+    for name in ["vendor", "product", "version"] {
+        let mut field = XMLElement::new(name);
+        field.add_text(format!("Some {name}")).unwrap();
+        header.add_child(field).unwrap();
+    }
+
+    header
+}
+
+#[test]
+fn test_shared_subtrees() {
+    let header = header();
+
+    let mut xml = XMLBuilder::new().build();
+    let mut batch = XMLElement::new("batch");
+    for _ in 0..2 {
+        let mut record = XMLElement::new("record");
+        record.add_child(header.clone()).unwrap();
+        batch.add_child(record).unwrap();
+    }
+
+    // Clones share the children of the fragment
+    let records = batch.children();
+    assert_eq!(
+        records[0].children()[0].children().as_ptr(),
+        header.children().as_ptr(),
+        "Both values does not match..."
+    );
+    assert_eq!(
+        records[1].children()[0].children().as_ptr(),
+        header.children().as_ptr(),
+        "Both values does not match..."
+    );
+
+    xml.set_root_element(batch);
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    let record = "\t<record>
+\t\t<header>
+\t\t\t<vendor>Some vendor</vendor>
+\t\t\t<product>Some product</product>
+\t\t\t<version>Some version</version>
+\t\t</header>
+\t</record>\n";
+    let expected = format!("<?xml version=\"1.0\"?>\n<batch>\n{record}{record}</batch>\n");
+    let res = std::str::from_utf8(&writer).unwrap();
+
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_shared_subtrees_copy_on_write() {
+    let header = header();
+
+    let mut copy = header.clone();
+    copy.children_mut()[0].set_text("Another vendor".into());
+    copy.add_child(XMLElement::new("license")).unwrap();
+
+    assert_ne!(
+        copy.children().as_ptr(),
+        header.children().as_ptr(),
+        "Both values should not match..."
+    );
+    assert_eq!(header.children().len(), 3, "Both values does not match...");
+    assert_eq!(copy.children().len(), 4, "Both values does not match...");
+    assert_eq!(
+        header.children()[0].text(),
+        Some("Some vendor"),
+        "Both values does not match..."
+    );
+    assert_eq!(
+        copy.children()[0].text(),
+        Some("Another vendor"),
+        "Both values does not match..."
+    );
+}
@@ -0,0 +1,112 @@
+use std::io::{BufRead, BufReader, Read};
+
+mod common;
+
+use common::render;
+use xml_builder::{RenderOptions, XMLSplitter};
+
+const CATALOG: &str = r#"<?xml version="1.0"?>
+<catalog xmlns="urn:catalog" xmlns:p="urn:price">
+    <header><product>Not a record</product></header>
+    <product id="1"><p:price>10</p:price></product>
+    <product id="2" xmlns="urn:other"><name>Second</name></product>
+    <section xmlns:p="urn:section-price">
+        <product id="3" />
+    </section>
+</catalog>"#;
+
+#[test]
+fn test_split_records() {
+    let records: Vec<String> = XMLSplitter::new(CATALOG.as_bytes(), "/catalog/product")
+        .unwrap()
+        .map(|record| render(&record.unwrap(), &RenderOptions::compact()))
+        .collect();
+
+    let expected = vec![
+        "<product xmlns=\"urn:catalog\" xmlns:p=\"urn:price\" id=\"1\"><p:price>10</p:price></product>",
+        "<product xmlns:p=\"urn:price\" id=\"2\" xmlns=\"urn:other\"><name>Second</name></product>",
+    ];
+    assert_eq!(records, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_split_wildcard() {
+    let records: Vec<String> = XMLSplitter::new(CATALOG.as_bytes(), "/catalog/*/product")
+        .unwrap()
+        .map(|record| render(&record.unwrap(), &RenderOptions::compact()))
+        .collect();
+
+    let expected = vec![
+        "<product xmlns=\"urn:catalog\" xmlns:p=\"urn:price\">Not a record</product>",
+        "<product xmlns=\"urn:catalog\" xmlns:p=\"urn:section-price\" id=\"3\" />",
+    ];
+    assert_eq!(records, expected, "Both values does not match...");
+}
+
+/// Endless source of records, ensuring the splitter never reads the whole document.
+struct Products {
+    remaining: usize,
+    buffer: Vec<u8>,
+}
+
+impl Read for Products {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+
+        Ok(length)
+    }
+}
+
+impl BufRead for Products {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.buffer.is_empty() && self.remaining > 0 {
+            self.remaining -= 1;
+            self.buffer = format!("<product id=\"{}\" />", self.remaining).into_bytes();
+        }
+
+        Ok(&self.buffer)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.buffer.drain(..amount);
+    }
+}
+
+#[test]
+fn test_split_stream() {
+    let source = BufReader::new(b"<catalog>".chain(Products {
+        remaining: usize::MAX,
+        buffer: Vec::new(),
+    }));
+
+    let mut splitter = XMLSplitter::new(source, "/catalog/product").unwrap();
+    for _ in 0..10_000 {
+        assert_eq!(splitter.next_record().unwrap().unwrap().name(), "product");
+    }
+}
+
+#[test]
+fn test_split_mixed_content() {
+    let input = "<catalog><product id=\"1\" /><product>Mixed <b>content</b></product><product id=\"3\" /></catalog>";
+    let mut splitter = XMLSplitter::new(input.as_bytes(), "/catalog/product").unwrap();
+
+    assert_eq!(
+        splitter.next_record().unwrap().unwrap().attributes()[0].1,
+        "1"
+    );
+    assert!(splitter.next_record().is_err());
+    assert_eq!(
+        splitter.next_record().unwrap().unwrap().attributes()[0].1,
+        "3"
+    );
+    assert!(splitter.next_record().unwrap().is_none());
+}
+
+#[test]
+fn test_invalid_path() {
+    assert!(XMLSplitter::new("<a />".as_bytes(), "catalog").is_err());
+    assert!(XMLSplitter::new("<a />".as_bytes(), "/catalog//product").is_err());
+}
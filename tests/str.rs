@@ -0,0 +1,44 @@
+use xml_builder::{XMLBuilder, XMLElement, XMLStr};
+
+#[test]
+fn test_static_and_shared_strings() {
+    let mut xml = XMLBuilder::new().build();
+
+    let kind = XMLStr::shared("vendor");
+    let mut house = XMLElement::from_name("house");
+    for i in 1..=2 {
+        let mut room = XMLElement::from_name("room");
+        room.add_attribute_str("number", i.to_string());
+        room.add_attribute_str("kind", kind.clone());
+        house.add_child(room).unwrap();
+    }
+    xml.set_root_element(house);
+
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+
+    let expected = "<?xml version=\"1.0\"?>
+<house>
+\t<room number=\"1\" kind=\"vendor\" />
+\t<room number=\"2\" kind=\"vendor\" />
+</house>\n";
+    let res = std::str::from_utf8(&writer).unwrap();
+
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_static_value_escaping() {
+    let mut element = XMLElement::from_name("company");
+    element.add_attribute_str("name", "Tom & Jerry");
+    element.add_attribute_str("kind", "cartoon");
+
+    let (name, value) = &element.attributes()[0];
+    assert_eq!(*name, "name", "Both values does not match...");
+    assert_eq!(*value, "Tom &amp; Jerry", "Both values does not match...");
+    assert_eq!(
+        element.attributes()[1].1,
+        "cartoon",
+        "Both values does not match..."
+    );
+}
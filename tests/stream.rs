@@ -0,0 +1,70 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use xml_builder::{XMLBuilder, XMLElement, XMLError};
+
+/// Writer sharing its output, so that it can be checked while the stream is opened.
+#[derive(Clone, Default)]
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl SharedWriter {
+    fn output(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_stream() {
+    let output = SharedWriter::default();
+
+    let mut root = XMLElement::new("stream:stream");
+    root.add_attribute("xmlns:stream", "http://etherx.jabber.org/streams");
+    root.add_attribute("to", "example.com");
+    let mut stream = XMLBuilder::new()
+        .indent(false)
+        .build_stream(output.clone(), &root)
+        .unwrap();
+
+    let opening = "<?xml version=\"1.0\"?>\n<stream:stream xmlns:stream=\"http://etherx.jabber.org/streams\" to=\"example.com\">\n";
+    assert_eq!(output.output(), opening, "Both values does not match...");
+
+    let mut message = XMLElement::new("message");
+    message.add_attribute("to", "romeo@example.net");
+    let mut body = XMLElement::new("body");
+    body.add_text("Art thou not Romeo?".into()).unwrap();
+    message.add_child(body).unwrap();
+    stream.send(&message).unwrap();
+
+    let stanza =
+        "<message to=\"romeo@example.net\">\n<body>Art thou not Romeo?</body>\n</message>\n";
+    assert_eq!(
+        output.output(),
+        format!("{opening}{stanza}"),
+        "Both values does not match..."
+    );
+
+    stream.send(&XMLElement::new("presence")).unwrap();
+    stream.close().unwrap();
+
+    let expected = format!("{opening}{stanza}<presence />\n</stream:stream>\n");
+    assert_eq!(output.output(), expected, "Both values does not match...");
+}
+
+#[test]
+fn test_stream_root_with_content() {
+    let mut root = XMLElement::new("stream");
+    root.add_text("text".into()).unwrap();
+
+    let res = XMLBuilder::new().build_stream(Vec::new(), &root);
+    assert!(matches!(res, Err(XMLError::WriterError(_))));
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::render;
+use xml_builder::{RenderOptions, XMLArena, XMLBuilder, XMLElement};
+
+fn document(text: &str) -> XMLElement {
+    let mut paragraph = XMLElement::new("p");
+    paragraph.add_text(text.to_owned()).unwrap();
+    let mut doc = XMLElement::new("doc");
+    doc.add_child(paragraph).unwrap();
+
+    doc
+}
+
+#[test]
+fn test_text_wrapping() {
+    let doc = document("The quick brown fox jumps over the lazy dog");
+
+    let expected =
+        "<doc>\n\t<p>\n\t\tThe quick brown\n\t\tfox jumps over the\n\t\tlazy dog\n\t</p>\n</doc>\n";
+    let res = render(&doc, &RenderOptions::pretty().text_width(20));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Text fitting on the line of its tags is left untouched
+    let expected = "<doc>\n\t<p>The quick brown fox jumps over the lazy dog</p>\n</doc>\n";
+    let res = render(&doc, &RenderOptions::pretty().text_width(51));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let expected =
+        "<doc>\n\t<p>\n\t\tThe quick brown fox jumps over the lazy dog\n\t</p>\n</doc>\n";
+    let res = render(&doc, &RenderOptions::pretty().text_width(50));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Compact renderings have no lines to wrap
+    let expected = "<doc><p>The quick brown fox jumps over the lazy dog</p></doc>";
+    let res = render(&doc, &RenderOptions::compact().text_width(20));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_long_words() {
+    let doc = document("a verylongwordexceedingthewidth b");
+
+    let expected =
+        "<doc>\n\t<p>\n\t\ta\n\t\tverylongwordexceedingthewidth\n\t\tb\n\t</p>\n</doc>\n";
+    let res = render(&doc, &RenderOptions::pretty().text_width(10));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_text_indentation() {
+    let doc = document("\n      if (a) {\n          b();\n\n      }\n    ");
+
+    let expected = "<doc>\n  <p>\n    if (a) {\n        b();\n\n    }\n  </p>\n</doc>\n";
+    let options = RenderOptions::pretty().indent_spaces(2).indent_text(true);
+    let res = render(&doc, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Without re-indentation, multi-line text is written as is
+    let expected = "<doc>\n  <p>\n      if (a) {\n          b();\n\n      }\n    </p>\n</doc>\n";
+    let res = render(&doc, &RenderOptions::pretty().indent_spaces(2));
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Single-line text stays on the line of its tags
+    let doc = document("single line");
+    let expected = "<doc>\n  <p>single line</p>\n</doc>\n";
+    let res = render(&doc, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_mixed_indentation() {
+    let options = RenderOptions::pretty().indent_spaces(2).indent_text(true);
+
+    // Lines sharing the same mix of tabulations and spaces are dedented
+    let doc = document("\n\t  if (a) {\n\t      b();\n\t  }\n");
+    let expected = "<doc>\n  <p>\n    if (a) {\n        b();\n    }\n  </p>\n</doc>\n";
+    let res = render(&doc, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Lines indented differently are left as is
+    let doc = document("\n\tif (a) {\n    b();\n\t}\n");
+    let expected = "<doc>\n  <p>\n    \tif (a) {\n        b();\n    \t}\n  </p>\n</doc>\n";
+    let res = render(&doc, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_wrapped_lines_keep_indentation() {
+    let doc = document("list:\n  one two three four");
+
+    let expected = "<doc>\n\t<p>\n\t\tlist:\n\t\t  one two\n\t\t  three four\n\t</p>\n</doc>\n";
+    let res = render(&doc, &RenderOptions::pretty().text_width(14));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_preserved_space() {
+    let text = "The quick brown fox\n  jumps over the lazy dog";
+    let options = RenderOptions::pretty().text_width(21).indent_text(true);
+
+    let mut doc = document(text);
+    doc.add_attribute("xml:space", "preserve");
+    let expected = format!("<doc xml:space=\"preserve\">\n\t<p>{text}</p>\n</doc>\n");
+    let res = render(&doc, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+
+    // Descendants can restore the default whitespace handling
+    let mut paragraph = XMLElement::new("p");
+    paragraph.add_attribute("xml:space", "default");
+    paragraph.add_text(text.to_owned()).unwrap();
+    let mut doc = XMLElement::new("doc");
+    doc.add_attribute("xml:space", "preserve");
+    doc.add_child(paragraph).unwrap();
+    let expected = "<doc xml:space=\"preserve\">\n\t<p xml:space=\"default\">\n\t\tThe quick brown fox\n\t\t  jumps over the\n\t\t  lazy dog\n\t</p>\n</doc>\n";
+    let res = render(&doc, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_text_consistency() {
+    let mut doc = document("The quick brown fox jumps over the lazy dog");
+    let mut code = XMLElement::new("code");
+    code.add_attribute("xml:space", "preserve");
+    code.add_text("\n  a\n    b\n".to_owned()).unwrap();
+    doc.add_child(code).unwrap();
+    let mut script = XMLElement::new("script");
+    script.add_text("\n  a\n    b\n".to_owned()).unwrap();
+    doc.add_child(script).unwrap();
+    let arena = XMLArena::from(&doc);
+
+    let options = RenderOptions::pretty().text_width(20).indent_text(true);
+    let expected = render(&doc, &options);
+
+    let mut xml = XMLBuilder::new().text_width(20).indent_text(true).build();
+    xml.set_root_element(doc.clone());
+    let mut writer: Vec<u8> = Vec::new();
+    xml.generate(&mut writer).unwrap();
+    let res = String::from_utf8(writer).unwrap();
+    let document = format!("<?xml version=\"1.0\"?>\n{expected}");
+    assert_eq!(res, document, "Both values does not match...");
+
+    let mut writer: Vec<u8> = Vec::new();
+    arena
+        .render(arena.root().unwrap(), &mut writer, &options)
+        .unwrap();
+    let res = String::from_utf8(writer).unwrap();
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let len = doc.rendered_len(&options).unwrap();
+    assert_eq!(len, expected.len(), "Both values does not match...");
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::render;
+use xml_builder::{RenderOptions, XMLArena, XMLAttributeWrapping, XMLBuilder, XMLElement};
+
+fn svg() -> XMLElement {
+    let mut svg = XMLElement::new("svg");
+    svg.add_attribute("xmlns", "http://www.w3.org/2000/svg");
+    svg.add_attribute("width", "100");
+    svg.add_attribute("height", "100");
+
+    let mut rect = XMLElement::new("rect");
+    for (name, value) in [("x", "10"), ("y", "10"), ("width", "80"), ("height", "80")] {
+        rect.add_attribute(name, value);
+    }
+    svg.add_child(rect).unwrap();
+
+    let mut circle = XMLElement::new("circle");
+    circle.add_attribute("r", "5");
+    svg.add_child(circle).unwrap();
+
+    svg
+}
+
+#[test]
+fn test_aligned_attributes() {
+    let expected = "<svg xmlns=\"http://www.w3.org/2000/svg\"\n     width=\"100\"\n     height=\"100\">\n\t<rect x=\"10\"\n\t      y=\"10\"\n\t      width=\"80\"\n\t      height=\"80\" />\n\t<circle r=\"5\" />\n</svg>\n";
+    let res = render(&svg(), &RenderOptions::pretty().max_line_width(40));
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_indented_attributes() {
+    let options = RenderOptions::pretty()
+        .indent_spaces(2)
+        .max_line_width(40)
+        .attribute_wrapping(XMLAttributeWrapping::Indented);
+    let expected = "<svg xmlns=\"http://www.w3.org/2000/svg\"\n  width=\"100\"\n  height=\"100\">\n  <rect x=\"10\"\n    y=\"10\"\n    width=\"80\"\n    height=\"80\" />\n  <circle r=\"5\" />\n</svg>\n";
+    let res = render(&svg(), &options);
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_wrapping_width() {
+    let mut element = XMLElement::new("a");
+    element.add_attribute("b", "1");
+    element.add_attribute("c", "2");
+
+    // The start tag takes exactly 17 characters
+    let res = render(&element, &RenderOptions::pretty().max_line_width(17));
+    assert_eq!(
+        res, "<a b=\"1\" c=\"2\" />\n",
+        "Both values does not match..."
+    );
+
+    let res = render(&element, &RenderOptions::pretty().max_line_width(16));
+    assert_eq!(
+        res, "<a b=\"1\"\n   c=\"2\" />\n",
+        "Both values does not match..."
+    );
+
+    let options = RenderOptions::pretty()
+        .max_line_width(16)
+        .expand_empty_tags(true);
+    let res = render(&element, &options);
+    assert_eq!(
+        res, "<a b=\"1\" c=\"2\"></a>\n",
+        "Both values does not match..."
+    );
+
+    // Compact renderings have no lines to wrap
+    let res = render(&element, &RenderOptions::compact().max_line_width(16));
+    assert_eq!(
+        res, "<a b=\"1\" c=\"2\" />",
+        "Both values does not match..."
+    );
+}
+
+#[test]
+fn test_wrapping_inline_elements() {
+    let mut item = XMLElement::new("item");
+    item.add_attribute("id", "1");
+    item.add_attribute("name", "first");
+    item.add_child(XMLElement::new("value")).unwrap();
+    let mut list = XMLElement::new("list");
+    list.add_child(item).unwrap();
+
+    // Elements kept on a single line are not wrapped
+    let options = RenderOptions::pretty().inline_width(80).max_line_width(20);
+    let expected = "<list><item id=\"1\" name=\"first\"><value /></item></list>\n";
+    let res = render(&list, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+
+    let options = RenderOptions::pretty().inline_width(43).max_line_width(20);
+    let expected = "<list>\n\t<item id=\"1\" name=\"first\"><value /></item>\n</list>\n";
+    let res = render(&list, &options);
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_wrapping_consistency() {
+    let svg = svg();
+    let arena = XMLArena::from(&svg);
+
+    for wrapping in [
+        XMLAttributeWrapping::Aligned,
+        XMLAttributeWrapping::Indented,
+    ] {
+        let builder = || {
+            XMLBuilder::new()
+                .max_line_width(40)
+                .attribute_wrapping(wrapping)
+        };
+        let options = RenderOptions::pretty()
+            .max_line_width(40)
+            .attribute_wrapping(wrapping);
+        let expected = render(&svg, &options);
+
+        let mut xml = builder().build();
+        xml.set_root_element(svg.clone());
+        let mut writer: Vec<u8> = Vec::new();
+        xml.generate(&mut writer).unwrap();
+        let res = String::from_utf8(writer).unwrap();
+        let document = format!("<?xml version=\"1.0\"?>\n{expected}");
+        assert_eq!(res, document, "Both values does not match...");
+
+        let mut writer = builder().build_writer(Vec::new());
+        writer.start_element("svg").unwrap();
+        for (name, value) in svg.attributes() {
+            writer.attribute(name, value).unwrap();
+        }
+        for child in svg.children() {
+            writer.start_element(child.name()).unwrap();
+            for (name, value) in child.attributes() {
+                writer.attribute(name, value).unwrap();
+            }
+            writer.end_element(child.name()).unwrap();
+        }
+        writer.end_element("svg").unwrap();
+        let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(res, document, "Both values does not match...");
+
+        let mut writer: Vec<u8> = Vec::new();
+        arena
+            .render(arena.root().unwrap(), &mut writer, &options)
+            .unwrap();
+        let res = String::from_utf8(writer).unwrap();
+        assert_eq!(res, expected, "Both values does not match...");
+
+        let len = svg.rendered_len(&options).unwrap();
+        assert_eq!(len, expected.len(), "Both values does not match...");
+    }
+}
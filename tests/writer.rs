@@ -0,0 +1,177 @@
+use xml_builder::{XMLBuilder, XMLElement, XMLError};
+
+#[test]
+fn test_writer_matches_generate() {
+    let mut house = XMLElement::new("house");
+    house.add_attribute("rooms", "2");
+    let mut room = XMLElement::new("room");
+    room.add_attribute("name", "kitchen");
+    room.add_text("Tom &amp; Jerry".into()).unwrap();
+    house.add_child(room).unwrap();
+    house.add_child(XMLElement::new("garden")).unwrap();
+
+    let mut expected: Vec<u8> = Vec::new();
+    let mut xml = XMLBuilder::new().sort_attributes(true).build();
+    xml.set_root_element(house);
+    xml.generate(&mut expected).unwrap();
+
+    let mut writer = XMLBuilder::new()
+        .sort_attributes(true)
+        .build_writer(Vec::new());
+    writer.start_element("house").unwrap();
+    writer.attribute("rooms", "2").unwrap();
+    writer.start_element("room").unwrap();
+    writer.attribute("name", "kitchen").unwrap();
+    writer.text("Tom ").unwrap();
+    writer.text("& Jerry").unwrap();
+    writer.end_element("room").unwrap();
+    writer.start_element("garden").unwrap();
+    writer.end_element("garden").unwrap();
+    writer.end_element("house").unwrap();
+
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let expected = String::from_utf8(expected).unwrap();
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_writer_nodes() {
+    let mut writer = XMLBuilder::new()
+        .indent(false)
+        .expand_empty_tags(true)
+        .build_writer(Vec::new());
+    writer.comment(" inventory ").unwrap();
+    writer.start_element("items").unwrap();
+    writer.processing_instruction("sort", Some("asc")).unwrap();
+
+    let mut item = XMLElement::new("item");
+    item.add_attribute("id", "1");
+    writer.write_element(&item).unwrap();
+
+    writer.start_element("script").unwrap();
+    writer.cdata("a < b").unwrap();
+    writer.end_element("script").unwrap();
+    writer.end_element("items").unwrap();
+
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let expected = "<?xml version=\"1.0\"?>\n<!-- inventory -->\n<items>\n<?sort asc?>\n<item id=\"1\"></item>\n<script><![CDATA[a < b]]></script>\n</items>\n";
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_writer_rejects_malformed_calls() {
+    let mut writer = XMLBuilder::new().build_writer(Vec::new());
+    assert!(matches!(writer.text("a"), Err(XMLError::WriterError(_))));
+    assert!(matches!(
+        writer.attribute("a", "b"),
+        Err(XMLError::WriterError(_))
+    ));
+    assert!(matches!(
+        writer.start_element("1st"),
+        Err(XMLError::WriterError(_))
+    ));
+
+    writer.start_element("root").unwrap();
+    writer.attribute("a", "b").unwrap();
+    assert!(matches!(
+        writer.attribute("a", "c"),
+        Err(XMLError::WriterError(_))
+    ));
+    assert!(matches!(
+        writer.comment("a -- b"),
+        Err(XMLError::WriterError(_))
+    ));
+
+    writer.start_element("child").unwrap();
+    writer.attribute("late", "yes").unwrap();
+    writer.text("text").unwrap();
+    assert!(matches!(
+        writer.attribute("late", "no"),
+        Err(XMLError::WriterError(_))
+    ));
+    assert!(matches!(
+        writer.start_element("mixed"),
+        Err(XMLError::InsertError(_))
+    ));
+    assert!(matches!(
+        writer.end_element("root"),
+        Err(XMLError::WriterError(_))
+    ));
+    writer.end_element("child").unwrap();
+    assert!(matches!(
+        writer.text("mixed"),
+        Err(XMLError::InsertError(_))
+    ));
+    writer.end_element("root").unwrap();
+
+    assert!(matches!(
+        writer.start_element("second"),
+        Err(XMLError::WriterError(_))
+    ));
+    assert!(matches!(
+        writer.end_element("root"),
+        Err(XMLError::WriterError(_))
+    ));
+    assert!(writer.finish().is_ok());
+}
+
+#[test]
+fn test_writer_unfinished_document() {
+    let writer = XMLBuilder::new().build_writer(Vec::new());
+    assert!(matches!(writer.finish(), Err(XMLError::WriterError(_))));
+
+    let mut writer = XMLBuilder::new().build_writer(Vec::new());
+    writer.start_element("root").unwrap();
+    assert!(matches!(writer.finish(), Err(XMLError::WriterError(_))));
+}
+
+#[test]
+fn test_scoped_writer() {
+    let mut writer = XMLBuilder::new().build_writer(Vec::new());
+    writer
+        .element("house", |e| {
+            e.attr("rooms", 2);
+            e.element("room", |r| r.text("Tom & Jerry"));
+            e.element("garden", |_| {});
+        })
+        .unwrap();
+
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let expected = "<?xml version=\"1.0\"?>\n<house rooms=\"2\">\n\t<room>Tom &amp; Jerry</room>\n\t<garden />\n</house>\n";
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_scoped_writer_deferred_error() {
+    let mut writer = XMLBuilder::new().build_writer(Vec::new());
+    let res = writer.element("house", |e| {
+        e.text("text");
+        e.element("room", |_| {});
+        e.attr("late", true);
+    });
+
+    assert!(matches!(res, Err(XMLError::InsertError(_))));
+
+    // The element is closed anyway, so that the document can be ended
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let expected = "<?xml version=\"1.0\"?>\n<house>text</house>\n";
+    assert_eq!(res, expected, "Both values does not match...");
+}
+
+#[test]
+fn test_scoped_writer_nested_error() {
+    let mut writer = XMLBuilder::new().build_writer(Vec::new());
+    writer.start_element("house").unwrap();
+    let res = writer.element("room", |r| {
+        r.element("bed", |b| b.attr("1size", 2));
+        r.element("desk", |_| {});
+    });
+    assert!(matches!(res, Err(XMLError::WriterError(_))));
+    assert_eq!(writer.depth(), 1, "Both values does not match...");
+
+    writer.element("garden", |_| {}).unwrap();
+    writer.end_element("house").unwrap();
+    let res = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let expected = "<?xml version=\"1.0\"?>\n<house>\n\t<room>\n\t\t<bed />\n\t</room>\n\t<garden />\n</house>\n";
+    assert_eq!(res, expected, "Both values does not match...");
+}